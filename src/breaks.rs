@@ -0,0 +1,265 @@
+//! Time the planner keeps free of flexible tasks, like lunch or short breaks from work.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSeconds};
+
+use crate::{Config, DynamicTask, Schedule, TimeRange};
+
+/// A named break kept in every day's schedule, see [`Config::protect`].
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Break {
+    name: String,
+    rule: BreakRule,
+}
+
+/// When a [`Break`] happens.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum BreakRule {
+    /// At `time`, moved by up to `flexibility` either way when static tasks are in the way.
+    At {
+        time: TimeRange,
+        #[serde_as(as = "DurationSeconds<i64>")]
+        flexibility: Duration,
+    },
+    /// `length` after every `work` of tasks packed together, so dynamic tasks are moved apart
+    /// instead of keeping someone busy for longer than `work` without a break. Free time of a
+    /// day isn't cut up until there are tasks in it.
+    Every {
+        #[serde_as(as = "DurationSeconds<i64>")]
+        work: Duration,
+        #[serde_as(as = "DurationSeconds<i64>")]
+        length: Duration,
+    },
+}
+
+impl Break {
+    pub fn new(name: String, rule: BreakRule) -> Self {
+        Self { name, rule }
+    }
+
+    /// A break at `time` that can move by up to `flexibility`, like lunch.
+    pub fn at(name: String, time: TimeRange, flexibility: Duration) -> Self {
+        Self::new(name, BreakRule::At { time, flexibility })
+    }
+
+    /// A `length` break after every `work` of tasks packed together.
+    pub fn every(name: String, work: Duration, length: Duration) -> Self {
+        Self::new(name, BreakRule::Every { work, length })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_rule(&self) -> &BreakRule {
+        &self.rule
+    }
+}
+
+/// Puts the breaks at a time of `breaks` into the free time of `schedule` on the day of
+/// `config`, around the `busy` times of fixed tasks yet to be placed. Breaks repeating after
+/// work depend on the tasks placed, see [`after_work`].
+pub(crate) fn protect<'a>(
+    config: &Config,
+    breaks: &'a [Break],
+    mut schedule: Vec<Schedule<'a>>,
+    busy: &[TimeRange],
+) -> Vec<Schedule<'a>> {
+    for brk in breaks {
+        if let BreakRule::At { time, flexibility } = brk.rule {
+            let wanted = config.offset(time.start);
+            let length = time.to_duration();
+            let start = free_spans(config, &schedule, busy)
+                .into_iter()
+                .filter_map(|(from, to)| {
+                    let earliest = from.max(wanted - flexibility);
+                    let latest = (to - length).min(wanted + flexibility);
+                    (earliest <= latest).then(|| wanted.clamp(earliest, latest))
+                })
+                .min_by_key(|start| (*start - wanted).max(wanted - *start));
+
+            if let Some(start) = start {
+                carve(config, &mut schedule, brk, start, length);
+            }
+        }
+    }
+
+    schedule
+}
+
+/// The free ranges of `schedule` without `busy`, as offsets from the wake time.
+fn free_spans(
+    config: &Config,
+    schedule: &[Schedule],
+    busy: &[TimeRange],
+) -> Vec<(Duration, Duration)> {
+    let busy = busy
+        .iter()
+        .map(|time| {
+            let start = config.offset(time.start);
+            (start, start + time.to_duration())
+        })
+        .collect::<Vec<_>>();
+
+    let mut spans = vec![];
+    for entry in schedule {
+        let Schedule::Free(range) = entry else {
+            continue;
+        };
+        let mut from = config.offset(range.start);
+        let to = from + range.to_duration();
+
+        let mut inside = busy
+            .iter()
+            .filter(|(start, end)| *start < to && *end > from)
+            .collect::<Vec<_>>();
+        inside.sort();
+        for (start, end) in inside {
+            if from < *start {
+                spans.push((from, *start));
+            }
+            from = from.max(*end);
+        }
+        if from < to {
+            spans.push((from, to));
+        }
+    }
+    spans
+}
+
+/// Puts the breaks repeating after work of `breaks` into the placed `schedule` on the day of
+/// `config`. Wherever tasks are packed together for longer than `work`, the break goes right
+/// after the task keeping someone busy for too long and the tasks after it move `length`
+/// later, as long as they're flexible and the free time right after them has room for it.
+/// Tasks apart by at least `length`, or by another break, already have a break between them.
+pub(crate) fn after_work<'a>(
+    config: &Config,
+    breaks: &'a [Break],
+    mut schedule: Vec<Schedule<'a>>,
+) -> Vec<Schedule<'a>> {
+    let at = |offset: Duration| config.wake_time + offset;
+    for brk in breaks {
+        let BreakRule::Every { work, length } = brk.rule else {
+            continue;
+        };
+        if work <= Duration::zero() || length <= Duration::zero() {
+            continue;
+        }
+
+        // start of the tasks packed together, the end of the last one and the index after it
+        let mut busy: Option<(Duration, Duration, usize)> = None;
+        let mut i = 0;
+        while i < schedule.len() {
+            let time = schedule[i].get_time();
+            let from = config.offset(time.start);
+            let to = from + time.to_duration();
+            match schedule[i] {
+                Schedule::Break(..) => busy = None,
+                Schedule::Static(_) | Schedule::DynamicTask(..) | Schedule::DynamicPart { .. } => {
+                    busy = match busy {
+                        Some((start, end, after)) if from - end < length => {
+                            if to - start > work && postpone(&mut schedule, after, length) {
+                                schedule.insert(
+                                    after,
+                                    Schedule::Break(brk, TimeRange::new(at(end), at(end + length))),
+                                );
+                                i += 1;
+                                Some((from + length, to + length, i + 1))
+                            } else {
+                                Some((start, end.max(to), i + 1))
+                            }
+                        }
+                        _ => Some((from, to, i + 1)),
+                    };
+                }
+                Schedule::Buffer(_) | Schedule::Travel(_) | Schedule::Free(_) => {}
+            }
+            i += 1;
+        }
+    }
+    schedule
+}
+
+/// Moves the entries of `schedule` from `from` up to the next free time `length` later, into
+/// that free time. Returns whether they could be moved, which static tasks, fixed tasks and
+/// breaks can't be.
+fn postpone(schedule: &mut Vec<Schedule>, from: usize, length: Duration) -> bool {
+    let Some(free) = (from..schedule.len()).find(|&i| matches!(schedule[i], Schedule::Free(_)))
+    else {
+        return false;
+    };
+    let Schedule::Free(room) = schedule[free] else {
+        unreachable!()
+    };
+    let movable = schedule[from..free].iter().all(|entry| {
+        matches!(
+            entry,
+            Schedule::DynamicTask(DynamicTask::Flexible { .. }, _)
+                | Schedule::DynamicPart { .. }
+                | Schedule::Buffer(_)
+                | Schedule::Travel(_)
+        )
+    });
+    let packed = schedule[from..=free]
+        .windows(2)
+        .all(|pair| pair[0].get_time().end == pair[1].get_time().start);
+    if !movable || !packed || room.to_duration() < length {
+        return false;
+    }
+
+    for entry in &mut schedule[from..free] {
+        if let Schedule::DynamicTask(_, time)
+        | Schedule::DynamicPart { time, .. }
+        | Schedule::Buffer(time)
+        | Schedule::Travel(time) = entry
+        {
+            *time = TimeRange::new(time.start + length, time.end + length);
+        }
+    }
+    if room.to_duration() == length {
+        schedule.remove(free);
+    } else {
+        schedule[free] = Schedule::Free(TimeRange::new(room.start + length, room.end));
+    }
+    true
+}
+
+/// Takes `length` from `start` on out of the free range of `schedule` holding it for `brk`.
+fn carve<'a>(
+    config: &Config,
+    schedule: &mut Vec<Schedule<'a>>,
+    brk: &'a Break,
+    start: Duration,
+    length: Duration,
+) {
+    let at = |offset: Duration| config.wake_time + offset;
+    let i = schedule.iter().position(|entry| match entry {
+        Schedule::Free(range) => {
+            let from = config.offset(range.start);
+            from <= start && start + length <= from + range.to_duration()
+        }
+        _ => false,
+    });
+    let Some(i) = i else {
+        return;
+    };
+    let Schedule::Free(free) = schedule[i] else {
+        unreachable!()
+    };
+
+    let mut entries = vec![];
+    let from = config.offset(free.start);
+    if from < start {
+        entries.push(Schedule::Free(TimeRange::new(free.start, at(start))));
+    }
+    entries.push(Schedule::Break(
+        brk,
+        TimeRange::new(at(start), at(start + length)),
+    ));
+    if start + length < from + free.to_duration() {
+        entries.push(Schedule::Free(TimeRange::new(at(start + length), free.end)));
+    }
+    schedule.splice(i..=i, entries);
+}
@@ -0,0 +1,31 @@
+//! Where the planner gets the current time from.
+
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time, every decision of a [`Planner`](crate::Planner) depending on
+/// the date or time goes through it.
+pub trait Clock: Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stuck at a single instant, for tests or to preview another day.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+    path::PathBuf,
+};
+
+use chrono::{Duration, NaiveDate};
+use uuid::Uuid;
+
+use crate::{PartOfDay, Rule, TimeRange};
+
+#[derive(Debug)]
+pub enum LuniteError {
+    /// A day index past the seven days of the week.
+    DayOutOfRange(usize),
+    /// There is no task at the index.
+    TaskNotFound(usize),
+    DateInPast(NaiveDate),
+    /// A task's deadline is before it can start.
+    DeadlineBeforeStart {
+        start: NaiveDate,
+        deadline: NaiveDate,
+    },
+    NotEnoughFreeTime {
+        task_uuid: Uuid,
+        needed: Duration,
+        available: Duration,
+    },
+    /// The fixed time of a task is already taken.
+    TimeNotFree {
+        task_uuid: Uuid,
+        time: TimeRange,
+    },
+    /// The task overlaps the static task `task_uuid` called `name` at `time`.
+    Conflict {
+        task_uuid: Uuid,
+        name: String,
+        time: TimeRange,
+    },
+    /// The task isn't within the waking hours of the day.
+    OutsideOfDay {
+        time: TimeRange,
+        day: TimeRange,
+    },
+    /// Expected one of the named parts of day, not [`PartOfDay::Fixed`].
+    ExpectedNamedPart,
+    /// A recurrence rule that never repeats, like every 0 days.
+    InvalidRule(Rule),
+    /// Parts of `min` to `max`, at most `max_parts` of them, can't make up a task of `length`.
+    InvalidChunks {
+        length: Duration,
+        min: Option<Duration>,
+        max: Option<Duration>,
+        max_parts: Option<usize>,
+    },
+    /// The `nth` part of a task, `length` long, is outside of the task's chunk limits.
+    InvalidChunk {
+        length: Duration,
+        nth: usize,
+    },
+    /// Expected a [`DynamicTask::Flexible`](crate::DynamicTask::Flexible).
+    ExpectedFlexible,
+    /// Chunk limits for a task that can't be split.
+    ExpectedSplittable,
+    /// `input` couldn't be understood as a `kind`.
+    Parse {
+        kind: ParseKind,
+        input: String,
+    },
+    /// A calendar event or to-do without the property `name` it needs.
+    MissingProperty(&'static str),
+    /// A calendar property with a value that isn't valid for it.
+    InvalidProperty {
+        name: String,
+        value: String,
+    },
+    /// A recurrence rule of a calendar event that isn't weekly and forever.
+    UnsupportedRule(String),
+    UnknownTimeZone(String),
+    /// A calendar event taking up whole days instead of a time.
+    AllDayEvent,
+    /// A calendar to-do with a time, only all-day ones become tasks.
+    TimedTodo,
+    /// A calendar event ending before it starts or going on for a day or more.
+    EventTooLong(Duration),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Json(serde_json::Error),
+    /// A planner file written by a newer version.
+    UnsupportedVersion(u64),
+    /// A planner file that couldn't be migrated from `version`.
+    Migration {
+        version: u64,
+        message: String,
+    },
+}
+
+impl Display for LuniteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DayOutOfRange(n) => write!(f, "Expected n <= 6, n is {n}"),
+            Self::TaskNotFound(i) => write!(f, "There is no task at {i}"),
+            Self::DateInPast(date) => write!(f, "Task can't start in the past, on {date}"),
+            Self::DeadlineBeforeStart { start, deadline } => {
+                write!(f, "The deadline {deadline} is before the task starts on {start}")
+            }
+            Self::NotEnoughFreeTime {
+                task_uuid,
+                needed,
+                available,
+            } => write!(
+                f,
+                "There isn't enough free time for {task_uuid}, it needs {} minutes and only {} are available",
+                needed.num_minutes(),
+                available.num_minutes()
+            ),
+            Self::TimeNotFree { task_uuid, time } => {
+                write!(f, "The time {time} of {task_uuid} isn't free")
+            }
+            Self::Conflict { name, time, .. } => write!(f, "The task clashes with {name} at {time}"),
+            Self::OutsideOfDay { time, day } => {
+                write!(f, "The task at {time} isn't within the day, {day}")
+            }
+            Self::ExpectedNamedPart => write!(f, "Didn't expect Fixed"),
+            Self::InvalidRule(rule) => write!(f, "The rule {rule:?} never repeats"),
+            Self::InvalidChunks {
+                length,
+                min,
+                max,
+                max_parts,
+            } => {
+                let minutes = |duration: &Option<Duration>| match duration {
+                    Some(duration) => duration.num_minutes().to_string(),
+                    None => "any".to_string(),
+                };
+                write!(
+                    f,
+                    "Parts of {} to {} minutes can't make up {} minutes",
+                    minutes(min),
+                    minutes(max),
+                    length.num_minutes()
+                )?;
+                match max_parts {
+                    Some(parts) => write!(f, " in at most {parts} parts"),
+                    None => Ok(()),
+                }
+            }
+            Self::InvalidChunk { length, nth } => write!(
+                f,
+                "Part {nth} of {} minutes is outside of the task's chunk limits",
+                length.num_minutes()
+            ),
+            Self::ExpectedFlexible => write!(f, "Expected a flexible dynamic task"),
+            Self::ExpectedSplittable => write!(f, "Expected a task that can be split"),
+            Self::Parse { kind, input } => match kind {
+                ParseKind::Time => write!(f, "{input:?} isn't a valid time"),
+                ParseKind::Range => {
+                    write!(f, "Expected a range like 14:00-16:00, got {input:?}")
+                }
+                ParseKind::EmptyRange => write!(f, "The range {input:?} is empty"),
+                ParseKind::Duration => write!(
+                    f,
+                    "Expected a positive duration like 90m or 1h30m, got {input:?}"
+                ),
+                ParseKind::Weekday => write!(f, "{input:?} isn't a weekday"),
+                ParseKind::Date => write!(f, "{input:?} isn't a date"),
+                ParseKind::Strategy => write!(
+                    f,
+                    "Unknown strategy {input:?}, expected one of first-fit, best-fit, earliest-deadline-first, spread-evenly, optimal"
+                ),
+                ParseKind::Rollover => write!(
+                    f,
+                    "Unknown rollover {input:?}, expected one of keep, drop, next-free-day, overdue"
+                ),
+                ParseKind::Rule => write!(f, "{input:?} isn't a recurrence rule"),
+                ParseKind::Calendar => write!(
+                    f,
+                    "Expected the calendar to start with BEGIN:VCALENDAR, got {input:?}"
+                ),
+            },
+            Self::MissingProperty(name) => write!(f, "The {name} property is missing"),
+            Self::InvalidProperty { name, value } => write!(f, "Invalid {name} {value:?}"),
+            Self::UnsupportedRule(rule) => {
+                write!(f, "The recurrence rule {rule:?} isn't supported")
+            }
+            Self::UnknownTimeZone(zone) => write!(f, "Unknown time zone {zone:?}"),
+            Self::AllDayEvent => write!(f, "All-day events aren't supported"),
+            Self::TimedTodo => write!(f, "Only all-day to-dos are supported"),
+            Self::EventTooLong(length) => write!(
+                f,
+                "Events of {} minutes aren't supported, only ones shorter than a day",
+                length.num_minutes()
+            ),
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Json(e) => write!(f, "Planner file is malformed: {e}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Planner file has version {version}, only versions up to {} are supported",
+                crate::PLANNER_VERSION
+            ),
+            Self::Migration { version, message } => {
+                write!(f, "Couldn't migrate planner file from version {version}: {message}")
+            }
+        }
+    }
+}
+
+/// What [`LuniteError::Parse`] expected its input to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseKind {
+    Time,
+    Range,
+    /// A range starting and ending at the same time.
+    EmptyRange,
+    Duration,
+    Weekday,
+    Date,
+    Strategy,
+    Rollover,
+    Rule,
+    /// An iCalendar, which has to start with BEGIN:VCALENDAR.
+    Calendar,
+}
+
+impl Error for LuniteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for LuniteError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Something that came up while placing a day's dynamic tasks.
+#[derive(Debug)]
+pub enum ScheduleDiagnostic {
+    /// The task couldn't be placed at all.
+    Unplaced(LuniteError),
+    /// The task was placed, but not (entirely) inside of its part of day.
+    OutsidePartOfDay { task_uuid: Uuid, around: PartOfDay },
+    /// The task was left out to make room for tasks of higher priority.
+    Preempted { task_uuid: Uuid },
+}
+
+impl ScheduleDiagnostic {
+    pub fn task_uuid(&self) -> Option<&Uuid> {
+        match self {
+            Self::Unplaced(LuniteError::NotEnoughFreeTime { task_uuid, .. })
+            | Self::Unplaced(LuniteError::TimeNotFree { task_uuid, .. })
+            | Self::OutsidePartOfDay { task_uuid, .. }
+            | Self::Preempted { task_uuid } => Some(task_uuid),
+            Self::Unplaced(_) => None,
+        }
+    }
+
+    /// Whether the task is missing from the schedule, as opposed to just being placed
+    /// somewhere it didn't ask for.
+    pub fn is_unplaced(&self) -> bool {
+        matches!(self, Self::Unplaced(_) | Self::Preempted { .. })
+    }
+}
+
+impl Display for ScheduleDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unplaced(e) => write!(f, "{e}"),
+            Self::OutsidePartOfDay { task_uuid, around } => {
+                write!(
+                    f,
+                    "Couldn't fit {task_uuid} into {around:?}, placed it outside of it"
+                )
+            }
+            Self::Preempted { task_uuid } => {
+                write!(f, "{task_uuid} was preempted by higher priority tasks")
+            }
+        }
+    }
+}
@@ -0,0 +1,766 @@
+//! iCalendar (RFC 5545) export of schedules and import of tasks.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Weekday};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use uuid::Uuid;
+
+use crate::{
+    render, Config, DynamicTask, LuniteError, ParseKind, PartOfDay, Planner, Recurrence, Rule,
+    Schedule, StaticTask, Task, TimeRange,
+};
+
+impl Planner {
+    /// Exports the schedules of `dates` as an iCalendar. Static and recurring tasks become
+    /// repeating events from the first of `dates` they happen on, dynamic tasks become single
+    /// events. Times are in the planner's time zone, described by a VTIMEZONE unless it's UTC.
+    pub fn to_ics(
+        &self,
+        dates: impl IntoIterator<Item = NaiveDate>,
+    ) -> Result<String, LuniteError> {
+        let dates = dates.into_iter().collect::<Vec<_>>();
+        let mut calendar = Calendar::new(self.clock.now().naive_utc(), self.config.get_time_zone());
+        if let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) {
+            calendar.time_zone(*first, *last);
+        }
+        let plan = self.deadline_plan();
+        let mut statics = HashSet::new();
+        for date in dates {
+            let weekday = date.weekday().num_days_from_monday() as usize;
+            for task in &self.days[weekday].static_tasks {
+                if statics.insert(task.task.uuid) {
+                    let (start, end) = self.config.anchor(date, &task.time);
+                    calendar.push(Event {
+                        uid: task.task.uuid.to_string(),
+                        summary: task.task.name.clone(),
+                        description: &task.task.description,
+                        location: task.task.location.as_deref(),
+                        start,
+                        end,
+                        rrule: Some(format!("FREQ=WEEKLY;BYDAY={}", byday(start.weekday()))),
+                        exdates: self
+                            .calendar
+                            .iter()
+                            .filter(|(_, plan)| plan.skipped.contains(&task.task.uuid))
+                            .map(|(date, _)| self.config.anchor(*date, &task.time).0)
+                            .collect(),
+                    });
+                }
+            }
+            for task in &self.recurring {
+                let recurrence = task.get_recurrence();
+                if recurrence.occurs_on(date) && statics.insert(task.task.task.uuid) {
+                    let (start, end) = self.config.anchor(date, &task.task.time);
+                    calendar.push(Event {
+                        uid: task.task.task.uuid.to_string(),
+                        summary: task.task.task.name.clone(),
+                        description: &task.task.task.description,
+                        location: task.task.task.location.as_deref(),
+                        start,
+                        end,
+                        rrule: Some(rrule(&self.config, recurrence, start.date())),
+                        exdates: recurrence
+                            .get_exceptions()
+                            .iter()
+                            .map(|date| self.config.anchor(*date, &task.task.time).0)
+                            .collect(),
+                    });
+                }
+            }
+
+            let (schedule, _) = self.schedule_planned(date, &plan);
+            for entry in &schedule {
+                let (uid, task) = match entry {
+                    Schedule::DynamicTask(task, _) => (task.get_task().uuid.to_string(), task),
+                    Schedule::DynamicPart { of, nth, .. } => {
+                        (format!("{}-{nth}", of.get_task().uuid), of)
+                    }
+                    Schedule::Static(_)
+                    | Schedule::Buffer(_)
+                    | Schedule::Travel(_)
+                    | Schedule::Break(..)
+                    | Schedule::Free(_) => continue,
+                };
+                let (start, end) = self.config.anchor(date, &entry.get_time());
+                calendar.push(Event {
+                    uid,
+                    summary: render::label(&schedule, entry),
+                    description: &task.get_task().description,
+                    location: task.get_task().location.as_deref(),
+                    start,
+                    end,
+                    rrule: None,
+                    exdates: vec![],
+                });
+            }
+        }
+
+        Ok(calendar.finish())
+    }
+}
+
+/// What [`Planner::import_ics`] did with a calendar.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Number of static tasks added.
+    pub statics: usize,
+    /// Number of dynamic tasks added.
+    pub dynamics: usize,
+    /// Summaries of the events and to-dos that weren't imported and why.
+    pub skipped: Vec<(String, LuniteError)>,
+}
+
+impl Planner {
+    /// Imports the events and to-dos of an iCalendar. Events repeating weekly become static
+    /// tasks on their weekdays, single timed events fixed dynamic tasks and all-day to-dos
+    /// with a duration flexible dynamic tasks around `around`. A to-do's due date becomes its
+    /// deadline, to-dos without a start can be done from today on. Anything else, or anything
+    /// outside of the waking hours or clashing with tasks already in the planner on any of its
+    /// days, is skipped as a whole and reported.
+    ///
+    /// Times in UTC or with a TZID are converted to the planner's time zone, times without
+    /// either are taken as times in it.
+    pub fn import_ics(
+        &mut self,
+        ics: &str,
+        around: PartOfDay,
+    ) -> Result<ImportReport, LuniteError> {
+        let components = parse_components(ics)?;
+        let mut report = ImportReport::default();
+
+        for component in &components {
+            let summary = component.value("SUMMARY").unwrap_or("untitled");
+            match self.import_component(component, around) {
+                Ok(Imported::Statics(n)) => report.statics += n,
+                Ok(Imported::Dynamic) => report.dynamics += 1,
+                Err(e) => report.skipped.push((summary.to_string(), e)),
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn import_component(
+        &mut self,
+        component: &Component,
+        around: PartOfDay,
+    ) -> Result<Imported, LuniteError> {
+        let task = |component: &Component| {
+            let uuid = component
+                .value("UID")
+                .and_then(|uid| Uuid::parse_str(uid).ok())
+                .unwrap_or_else(Uuid::new_v4);
+            Task {
+                uuid,
+                name: unescape(component.value("SUMMARY").unwrap_or("untitled")),
+                description: unescape(component.value("DESCRIPTION").unwrap_or("")),
+                padding: None,
+                location: component.value("LOCATION").map(unescape),
+            }
+        };
+        let time_zone = self.config.get_time_zone();
+        let start = component.date_time("DTSTART", time_zone)?;
+
+        if component.name == "VTODO" {
+            let due = component.date_time("DUE", time_zone)?.map(|due| match due {
+                IcsTime::Date(date) => date,
+                IcsTime::DateTime(due) => self.config.logical_date(due),
+            });
+            let date = match (start, due) {
+                (Some(IcsTime::Date(date)), _) => date,
+                (Some(IcsTime::DateTime(_)), _) => return Err(LuniteError::TimedTodo),
+                (None, Some(due)) => self.today().min(due),
+                (None, None) => return Err(LuniteError::MissingProperty("DUE")),
+            };
+            let length = component
+                .value("DURATION")
+                .ok_or(LuniteError::MissingProperty("DURATION"))
+                .and_then(parse_ics_duration)?;
+            // iCalendar priorities go from 1 (highest) to 9 (lowest), 0 is undefined
+            let priority = match component.value("PRIORITY").map(str::parse::<i32>) {
+                Some(Ok(priority @ 1..=9)) => 10 - priority,
+                _ => 0,
+            };
+
+            let mut todo = DynamicTask::new_flexible(task(component), date, length, around, false)
+                .priority(priority);
+            if let Some(due) = due {
+                todo = todo.deadline(due)?;
+            }
+            self.add_dynamic(todo)?;
+            return Ok(Imported::Dynamic);
+        }
+
+        let start = match start {
+            Some(IcsTime::DateTime(start)) => start,
+            Some(IcsTime::Date(_)) => return Err(LuniteError::AllDayEvent),
+            None => return Err(LuniteError::MissingProperty("DTSTART")),
+        };
+        let end = match (
+            component.date_time("DTEND", time_zone)?,
+            component.value("DURATION"),
+        ) {
+            (Some(IcsTime::DateTime(end)), _) => end,
+            (None, Some(duration)) => start + parse_ics_duration(duration)?,
+            _ => return Err(LuniteError::MissingProperty("DTEND")),
+        };
+        if end <= start || end - start >= Duration::days(1) {
+            return Err(LuniteError::EventTooLong(end - start));
+        }
+        let time = TimeRange::new(start.time(), end.time());
+        // events after midnight but before the bed time belong to the day before
+        let date = self.config.logical_date(start);
+
+        let Some(rrule) = component.value("RRULE") else {
+            let day = self.config.get_day_on(date);
+            if !time.subset(&day) {
+                return Err(LuniteError::OutsideOfDay { time, day });
+            }
+            let fixed = self.dynamic_tasks.iter().filter_map(|task| match task {
+                DynamicTask::Fixed {
+                    task, date: other, ..
+                } => (*other == date).then_some(task),
+                DynamicTask::Flexible { .. } => None,
+            });
+            let clash = self
+                .statics_on(date)
+                .into_iter()
+                .chain(fixed)
+                .find(|task| task.time.overlap(&time));
+            if let Some(clash) = clash {
+                return Err(LuniteError::Conflict {
+                    task_uuid: clash.task.uuid,
+                    name: clash.task.name.clone(),
+                    time: clash.time,
+                });
+            }
+
+            self.add_dynamic(DynamicTask::new_fixed(
+                StaticTask::new(task(component), time),
+                date,
+            ))?;
+            return Ok(Imported::Dynamic);
+        };
+
+        let mut weekdays = weekly_days(rrule, start.weekday())?;
+        if date != start.date() {
+            weekdays = weekdays.into_iter().map(|weekday| weekday.pred()).collect();
+        }
+        // every weekday is checked before any is added, so the event is imported in full or
+        // not at all
+        for weekday in &weekdays {
+            let day = self.config.for_weekday(*weekday).get_day();
+            if !time.subset(&day) {
+                return Err(LuniteError::OutsideOfDay { time, day });
+            }
+            let clash = self.days[weekday.num_days_from_monday() as usize]
+                .static_tasks
+                .iter()
+                .find(|task| task.time.overlap(&time))
+                .or_else(|| self.recurring_clash(*weekday, &time));
+            if let Some(clash) = clash {
+                return Err(LuniteError::Conflict {
+                    task_uuid: clash.task.uuid,
+                    name: clash.task.name.clone(),
+                    time: clash.time,
+                });
+            }
+        }
+        for (i, weekday) in weekdays.iter().enumerate() {
+            // every day needs its own task, only the first one keeps the uid
+            let mut task = task(component);
+            if i > 0 {
+                task.uuid = Uuid::new_v4();
+            }
+            self.add_static(
+                weekday.num_days_from_monday() as usize,
+                StaticTask::new(task, time),
+            )?;
+        }
+
+        Ok(Imported::Statics(weekdays.len()))
+    }
+}
+
+enum Imported {
+    Statics(usize),
+    Dynamic,
+}
+
+/// A VEVENT or VTODO with its properties and their parameters.
+struct Component {
+    name: String,
+    properties: HashMap<String, (String, String)>,
+}
+
+enum IcsTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Component {
+    fn value(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(|(_, value)| value.as_str())
+    }
+
+    /// Value of the parameter `name` of the property `property`.
+    fn param(&self, property: &str, name: &str) -> Option<&str> {
+        let (params, _) = self.properties.get(property)?;
+        params.split(';').find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.eq_ignore_ascii_case(name)
+                .then(|| value.trim_matches('"'))
+        })
+    }
+
+    /// Value of the date or date time property `name`, times in UTC or with a TZID converted
+    /// to `time_zone`.
+    fn date_time(&self, name: &str, time_zone: Tz) -> Result<Option<IcsTime>, LuniteError> {
+        let Some((_, value)) = self.properties.get(name) else {
+            return Ok(None);
+        };
+        let invalid = || LuniteError::InvalidProperty {
+            name: name.to_string(),
+            value: value.clone(),
+        };
+
+        if self
+            .param(name, "VALUE")
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+        {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map(|date| Some(IcsTime::Date(date)))
+                .map_err(|_| invalid());
+        }
+
+        let (local, zone) = match value.strip_suffix('Z') {
+            Some(utc) => (utc, Tz::UTC),
+            None => match self.param(name, "TZID") {
+                Some(tzid) => (
+                    value.as_str(),
+                    tzid.parse::<Tz>()
+                        .map_err(|_| LuniteError::UnknownTimeZone(tzid.to_string()))?,
+                ),
+                None => (value.as_str(), time_zone),
+            },
+        };
+        let local = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        // a time skipped by a clock change means the same as an hour later
+        let date_time = zone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                zone.from_local_datetime(&(local + Duration::hours(1)))
+                    .earliest()
+            })
+            .ok_or_else(invalid)?;
+        Ok(Some(IcsTime::DateTime(
+            date_time.with_timezone(&time_zone).naive_local(),
+        )))
+    }
+}
+
+/// Splits an iCalendar into its VEVENT and VTODO components.
+fn parse_components(ics: &str) -> Result<Vec<Component>, LuniteError> {
+    // lines starting with whitespace continue the previous one
+    let mut lines: Vec<String> = vec![];
+    for line in ics.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    if lines.first().map(|line| line.trim()) != Some("BEGIN:VCALENDAR") {
+        return Err(LuniteError::Parse {
+            kind: ParseKind::Calendar,
+            input: lines.first().cloned().unwrap_or_default(),
+        });
+    }
+
+    let mut components = vec![];
+    let mut current: Option<Component> = None;
+    let mut nested = 0;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        let name = name.to_uppercase();
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if matches!(value.trim(), "VEVENT" | "VTODO") => {
+                current = Some(Component {
+                    name: value.trim().to_string(),
+                    properties: HashMap::new(),
+                });
+            }
+            // alarms and such inside of a component
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) => components.extend(current.take()),
+            (_, Some(component)) if nested == 0 => {
+                component
+                    .properties
+                    .entry(name)
+                    .or_insert_with(|| (params.to_string(), value.trim().to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(components)
+}
+
+/// Weekdays an event starting on `start` repeats on, if `rrule` makes it repeat every week
+/// and forever.
+fn weekly_days(rrule: &str, start: Weekday) -> Result<Vec<Weekday>, LuniteError> {
+    let mut weekdays = vec![start];
+    let mut weekly = false;
+
+    for part in rrule.split(';') {
+        let (key, value) = part.split_once('=').unwrap_or((part, ""));
+        match (key.to_uppercase().as_str(), value) {
+            ("FREQ", "WEEKLY") => weekly = true,
+            ("INTERVAL", "1") | ("WKST", _) => {}
+            ("BYDAY", days) => {
+                weekdays = days
+                    .split(',')
+                    .map(|day| match day {
+                        "MO" => Ok(Weekday::Mon),
+                        "TU" => Ok(Weekday::Tue),
+                        "WE" => Ok(Weekday::Wed),
+                        "TH" => Ok(Weekday::Thu),
+                        "FR" => Ok(Weekday::Fri),
+                        "SA" => Ok(Weekday::Sat),
+                        "SU" => Ok(Weekday::Sun),
+                        _ => Err(LuniteError::UnsupportedRule(rrule.to_string())),
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            _ => return Err(LuniteError::UnsupportedRule(rrule.to_string())),
+        }
+    }
+
+    if weekly {
+        Ok(weekdays)
+    } else {
+        Err(LuniteError::UnsupportedRule(rrule.to_string()))
+    }
+}
+
+/// Parses an iCalendar duration like `PT1H30M` or `P1DT2H`.
+fn parse_ics_duration(duration: &str) -> Result<Duration, LuniteError> {
+    let invalid = || LuniteError::InvalidProperty {
+        name: String::from("DURATION"),
+        value: duration.to_string(),
+    };
+    let rest = duration
+        .strip_prefix('+')
+        .unwrap_or(duration)
+        .strip_prefix('P')
+        .ok_or_else(invalid)?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut time = false;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => time = true,
+            _ => {
+                let n = number.parse::<i64>().map_err(|_| invalid())?;
+                total += match (c, time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return Err(invalid()),
+                };
+                number.clear();
+            }
+        }
+    }
+
+    if total > Duration::zero() && number.is_empty() {
+        Ok(total)
+    } else {
+        Err(invalid())
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+struct Event<'a> {
+    uid: String,
+    summary: String,
+    description: &'a str,
+    location: Option<&'a str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+}
+
+struct Calendar {
+    stamp: NaiveDateTime,
+    time_zone: Tz,
+    ics: String,
+}
+
+impl Calendar {
+    fn new(stamp: NaiveDateTime, time_zone: Tz) -> Self {
+        let mut calendar = Self {
+            stamp,
+            time_zone,
+            ics: String::new(),
+        };
+        calendar.line("BEGIN:VCALENDAR");
+        calendar.line("VERSION:2.0");
+        calendar.line("PRODID:-//lunite//lunite//EN");
+        calendar
+    }
+
+    fn push(&mut self, event: Event) {
+        self.line("BEGIN:VEVENT");
+        self.line(&format!("UID:{}", event.uid));
+        self.line(&format!("DTSTAMP:{}Z", self.stamp.format("%Y%m%dT%H%M%S")));
+        self.line(&self.date_time("DTSTART", event.start));
+        self.line(&self.date_time("DTEND", event.end));
+        if let Some(rrule) = event.rrule {
+            self.line(&format!("RRULE:{rrule}"));
+        }
+        for exdate in event.exdates {
+            self.line(&self.date_time("EXDATE", exdate));
+        }
+        self.line(&format!("SUMMARY:{}", escape(&event.summary)));
+        if !event.description.is_empty() {
+            self.line(&format!("DESCRIPTION:{}", escape(event.description)));
+        }
+        if let Some(location) = event.location {
+            self.line(&format!("LOCATION:{}", escape(location)));
+        }
+        self.line("END:VEVENT");
+    }
+
+    /// Writes the VTIMEZONE of the calendar's time zone, valid from a year before `first`
+    /// until ten years after `last` so repeating events keep their wall clock time. Zones
+    /// changing their offset by the same yearly rules all that time get a STANDARD and a
+    /// DAYLIGHT observance repeating by them, others one observance for every change.
+    fn time_zone(&mut self, first: NaiveDate, last: NaiveDate) {
+        if self.time_zone == Tz::UTC {
+            return;
+        }
+
+        let from = first.and_time(NaiveTime::MIN) - Duration::days(366);
+        let to = last.and_time(NaiveTime::MIN) + Duration::days(3653);
+        self.line("BEGIN:VTIMEZONE");
+        self.line(&format!("TZID:{}", self.time_zone.name()));
+        let start = self.time_zone.offset_from_utc_datetime(&from);
+        let mut offset = start;
+        let mut changes = vec![];
+        for change in offset_changes(self.time_zone, from, to) {
+            let next = self.time_zone.offset_from_utc_datetime(&change);
+            changes.push((change, offset, next));
+            offset = next;
+        }
+        match yearly_rules(&changes) {
+            Some(rules) => {
+                for (change, rrule) in rules {
+                    self.observance(change, Some(&rrule));
+                }
+            }
+            None => {
+                // the offset at the start, then every change of it
+                self.observance((from, start, start), None);
+                for change in changes {
+                    self.observance(change, None);
+                }
+            }
+        }
+        self.line("END:VTIMEZONE");
+    }
+
+    /// Writes the observance of the offset `change`, repeating by `rrule` if any.
+    fn observance(&mut self, (at, from, to): Change, rrule: Option<&str>) {
+        let kind = if to.dst_offset().is_zero() {
+            "STANDARD"
+        } else {
+            "DAYLIGHT"
+        };
+        let (from_offset, to_offset) = (from.fix().local_minus_utc(), to.fix().local_minus_utc());
+        let local = at + Duration::seconds(from_offset as i64);
+        self.line(&format!("BEGIN:{kind}"));
+        self.line(&format!("DTSTART:{}", local.format("%Y%m%dT%H%M%S")));
+        if let Some(rrule) = rrule {
+            self.line(&format!("RRULE:{rrule}"));
+        }
+        self.line(&format!("TZOFFSETFROM:{}", utc_offset(from_offset)));
+        self.line(&format!("TZOFFSETTO:{}", utc_offset(to_offset)));
+        // zones without a name for the offset have abbreviations like +03
+        let name = to.abbreviation();
+        if !name.starts_with(['+', '-']) {
+            self.line(&format!("TZNAME:{name}"));
+        }
+        self.line(&format!("END:{kind}"));
+    }
+
+    /// The date time property `name` at the wall clock time `time`.
+    fn date_time(&self, name: &str, time: NaiveDateTime) -> String {
+        let time = time.format("%Y%m%dT%H%M%S");
+        if self.time_zone == Tz::UTC {
+            format!("{name}:{time}Z")
+        } else {
+            format!("{name};TZID={}:{time}", self.time_zone.name())
+        }
+    }
+
+    /// Writes `line` folded into lines of at most 75 octets, continuation lines start with a
+    /// space.
+    fn line(&mut self, line: &str) {
+        let mut len = 0;
+        for c in line.chars() {
+            if len + c.len_utf8() > 75 {
+                self.ics.push_str("\r\n ");
+                len = 1;
+            }
+            self.ics.push(c);
+            len += c.len_utf8();
+        }
+        self.ics.push_str("\r\n");
+    }
+
+    fn finish(mut self) -> String {
+        self.line("END:VCALENDAR");
+        self.ics
+    }
+}
+
+/// RRULE of `recurrence` for an event starting on `start`, its end being the last second of
+/// the end date in `config`'s time zone.
+fn rrule(config: &Config, recurrence: &Recurrence, start: NaiveDate) -> String {
+    let mut rrule = match *recurrence.get_rule() {
+        Rule::Days(n) => format!("FREQ=DAILY;INTERVAL={n}"),
+        Rule::Weekdays => String::from("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"),
+        Rule::Weeks(n) => format!("FREQ=WEEKLY;INTERVAL={n};BYDAY={}", byday(start.weekday())),
+        Rule::MonthlyOn(n, weekday) => format!("FREQ=MONTHLY;BYDAY={n}{}", byday(weekday)),
+    };
+    if let Some(end) = recurrence.get_end() {
+        let until = config.to_utc(end.and_hms_opt(23, 59, 59).unwrap());
+        rrule.push_str(&format!(";UNTIL={}Z", until.format("%Y%m%dT%H%M%S")));
+    }
+    rrule
+}
+
+/// A change of the offset of a time zone, the instant in UTC it happens at with the offsets
+/// before and after.
+type Change = (
+    NaiveDateTime,
+    <Tz as TimeZone>::Offset,
+    <Tz as TimeZone>::Offset,
+);
+
+/// The first of every kind of `changes` with the yearly RRULE it repeats by, if there are two
+/// kinds of them, one there and one back, and every change of a kind happens on the same
+/// weekday of the same week of the same month at the same wall clock time.
+fn yearly_rules(changes: &[Change]) -> Option<Vec<(Change, String)>> {
+    let mut rules: Vec<(Change, String)> = vec![];
+    for &(at, from, to) in changes {
+        let local = at + Duration::seconds(from.fix().local_minus_utc() as i64);
+        let date = local.date();
+        let week = if (date + Duration::days(7)).month() != date.month() {
+            -1
+        } else {
+            date.day0() as i32 / 7 + 1
+        };
+        let rrule = format!(
+            "FREQ=YEARLY;BYMONTH={};BYDAY={week}{}",
+            date.month(),
+            byday(date.weekday())
+        );
+
+        let kind = (from.fix(), to.fix());
+        match rules
+            .iter()
+            .find(|((_, from, to), _)| (from.fix(), to.fix()) == kind)
+        {
+            Some(((first, from, _), first_rrule)) => {
+                let first = *first + Duration::seconds(from.fix().local_minus_utc() as i64);
+                if *first_rrule != rrule || first.time() != local.time() {
+                    return None;
+                }
+            }
+            None => rules.push(((at, from, to), rrule)),
+        }
+    }
+    (rules.len() == 2).then_some(rules)
+}
+
+/// Instants in UTC between `from` and `to` the offset of `time_zone` changes at.
+fn offset_changes(time_zone: Tz, from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let offset = |utc: NaiveDateTime| time_zone.offset_from_utc_datetime(&utc).fix();
+    let mut changes = vec![];
+    let mut day = from;
+    while day < to {
+        let (mut before, mut after) = (day, day + Duration::days(1));
+        day = after;
+        if offset(before) == offset(after) {
+            continue;
+        }
+
+        // offsets change at most once a day
+        while after - before > Duration::seconds(1) {
+            let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+            if offset(middle) == offset(before) {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        changes.push(after);
+    }
+    changes
+}
+
+/// An offset from UTC of `seconds` like `+0130`.
+fn utc_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+fn byday(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
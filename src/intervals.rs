@@ -0,0 +1,219 @@
+//! Sets of time, used to work out what's left of a day after its busy parts.
+
+use chrono::{Duration, NaiveTime};
+
+use crate::{midnight, TimeRange};
+
+/// Sorted, disjoint spans `[start, end)` of time since some instant, like midnight.
+/// Spans touching each other are merged into one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Intervals(Vec<(Duration, Duration)>);
+
+impl Intervals {
+    /// The single span from `start` to `end`, empty if `end` isn't after `start`.
+    fn span(start: Duration, end: Duration) -> Self {
+        let mut intervals = Self::default();
+        intervals.insert(start, end);
+        intervals
+    }
+
+    fn insert(&mut self, start: Duration, end: Duration) {
+        if start >= end {
+            return;
+        }
+
+        let first = self.0.partition_point(|&(_, other_end)| other_end < start);
+        let last = self
+            .0
+            .partition_point(|&(other_start, _)| other_start <= end);
+        let merged = self.0[first..last]
+            .iter()
+            .fold((start, end), |(start, end), &(other_start, other_end)| {
+                (start.min(other_start), end.max(other_end))
+            });
+        self.0.splice(first..last, [merged]);
+    }
+
+    fn remove(&mut self, start: Duration, end: Duration) {
+        if start >= end {
+            return;
+        }
+
+        let first = self.0.partition_point(|&(_, other_end)| other_end <= start);
+        let last = self
+            .0
+            .partition_point(|&(other_start, _)| other_start < end);
+        let mut left = vec![];
+        if let Some(&(other_start, _)) = self.0[first..last].first() {
+            if other_start < start {
+                left.push((other_start, start));
+            }
+        }
+        if let Some(&(_, other_end)) = self.0[first..last].last() {
+            if end < other_end {
+                left.push((end, other_end));
+            }
+        }
+        self.0.splice(first..last, left);
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for &(start, end) in &other.0 {
+            union.insert(start, end);
+        }
+        union
+    }
+
+    fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        for &(start, end) in &other.0 {
+            difference.remove(start, end);
+        }
+        difference
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        self.difference(&self.difference(other))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn to_duration(&self) -> Duration {
+        self.0
+            .iter()
+            .fold(Duration::zero(), |total, &(start, end)| {
+                total + (end - start)
+            })
+    }
+
+    /// The spans as ranges of the day, `start` being the instant they're measured from.
+    fn ranges_from(&self, start: NaiveTime) -> Vec<TimeRange> {
+        self.0
+            .iter()
+            .map(|&(from, to)| TimeRange::new(start + from, start + to))
+            .collect()
+    }
+}
+
+/// Time of the day made up of any number of [`TimeRange`]s, for working out when someone is
+/// free or busy. Ranges overlapping or touching each other are merged, including the ones
+/// touching at midnight.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeRangeSet(Intervals);
+
+impl TimeRangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: TimeRange) {
+        for (start, end) in since_midnight(range) {
+            self.0.insert(start, end);
+        }
+    }
+
+    pub fn remove(&mut self, range: TimeRange) {
+        for (start, end) in since_midnight(range) {
+            self.0.remove(start, end);
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self(self.0.union(&other.0))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self(self.0.intersection(&other.0))
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        Self(self.0.difference(&other.0))
+    }
+
+    /// The parts of `window` that aren't in the set, in the order of `window`.
+    pub fn gaps(&self, window: &TimeRange) -> Vec<TimeRange> {
+        Intervals::span(Duration::zero(), window.to_duration())
+            .difference(&self.since(window.start))
+            .ranges_from(window.start)
+    }
+
+    /// The parts of the set inside of `window`, in the order of `window`.
+    pub fn within(&self, window: &TimeRange) -> Vec<TimeRange> {
+        Intervals::span(Duration::zero(), window.to_duration())
+            .intersection(&self.since(window.start))
+            .ranges_from(window.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// How long the ranges of the set take together.
+    pub fn to_duration(&self) -> Duration {
+        self.0.to_duration()
+    }
+
+    /// The ranges of the set by their start, the one going over midnight last.
+    pub fn iter(&self) -> impl Iterator<Item = TimeRange> {
+        let mut spans = self.0.clone();
+        let day = Duration::days(1);
+        if spans.0.len() > 1 && spans.0[0].0 == Duration::zero() && spans.0.last().unwrap().1 == day
+        {
+            let (_, end) = spans.0.remove(0);
+            spans.0.last_mut().unwrap().1 = day + end;
+        }
+
+        spans.ranges_from(midnight()).into_iter()
+    }
+
+    /// The set as spans since `start`, ranges before it going on from the end of the day.
+    fn since(&self, start: NaiveTime) -> Intervals {
+        let mut since = Intervals::default();
+        for range in self.iter() {
+            let mut from = range.start.signed_duration_since(start);
+            if from < Duration::zero() {
+                from += Duration::days(1);
+            }
+            let to = from + range.to_duration();
+            since.insert(from, to);
+            // the part going past the day from `start`, back at its beginning
+            since.insert(from - Duration::days(1), to - Duration::days(1));
+        }
+        since
+    }
+}
+
+/// `range` as spans since midnight, split in two if it goes over midnight.
+fn since_midnight(range: TimeRange) -> [(Duration, Duration); 2] {
+    let start = range.start.signed_duration_since(midnight());
+    let end = start + range.to_duration();
+    let day = Duration::days(1);
+    [(start, end.min(day)), (Duration::zero(), end - day)]
+}
+
+impl From<TimeRange> for TimeRangeSet {
+    fn from(range: TimeRange) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl FromIterator<TimeRange> for TimeRangeSet {
+    fn from_iter<I: IntoIterator<Item = TimeRange>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        set.extend(ranges);
+        set
+    }
+}
+
+impl Extend<TimeRange> for TimeRangeSet {
+    fn extend<I: IntoIterator<Item = TimeRange>>(&mut self, ranges: I) {
+        for range in ranges {
+            self.insert(range);
+        }
+    }
+}
@@ -1,14 +1,43 @@
-use chrono::{Datelike, Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+mod breaks;
+mod clock;
+mod error;
+mod ics;
+mod intervals;
+pub mod parse;
+mod recurrence;
+pub mod render;
+mod scheduler;
+mod storage;
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
 use uuid::Uuid;
 
+pub use breaks::{Break, BreakRule};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use error::{LuniteError, ParseKind, ScheduleDiagnostic};
+pub use ics::ImportReport;
+pub use intervals::TimeRangeSet;
+pub use recurrence::{Recurrence, RecurringTask, Rule};
+pub use scheduler::{
+    BestFit, EarliestDeadlineFirst, FirstFit, Optimal, Placement, Scheduler, SpreadEvenly, Strategy,
+};
+pub use storage::PLANNER_VERSION;
+
 macro_rules! day_creation {
     () => {
         Day {
             static_tasks: vec![],
-            static_done: vec![],
-            dynamic_tasks: vec![],
         }
     };
 }
@@ -23,11 +52,12 @@ pub enum PartOfDay {
 }
 
 impl PartOfDay {
-    pub fn fixed_from_part(&self) -> Result<Self, String> {
+    pub fn fixed_from_part(&self) -> Result<Self, LuniteError> {
         Ok(Self::Fixed(match self {
-            Self::Morning => {
-                TimeRange::new(NaiveTime::from_hms_opt(4, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap())
-            }
+            Self::Morning => TimeRange::new(
+                NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            ),
             Self::Afternoon => TimeRange::new(
                 NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
                 NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
@@ -36,16 +66,77 @@ impl PartOfDay {
                 NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
                 NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
             ),
-            Self::Night => TimeRange::new(NaiveTime::from_hms_opt(21, 0, 0).unwrap(), NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-            _ => return Err(format!("Didn't expect Fixed")),
+            Self::Night => TimeRange::new(
+                NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            ),
+            _ => return Err(LuniteError::ExpectedNamedPart),
         }))
     }
+
+    /// The time range this part of day covers.
+    fn window(&self) -> TimeRange {
+        match self {
+            Self::Fixed(range) => *range,
+            part => match part.fixed_from_part() {
+                Ok(Self::Fixed(range)) => range,
+                _ => unreachable!(),
+            },
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// What [`Planner::advance`] does with dynamic tasks that weren't done in time.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Rollover {
+    /// Leave them as they are, on the dates they weren't done on.
+    #[default]
+    Keep,
+    /// Remove them.
+    Drop,
+    /// Move them to the first day from today they fit into.
+    NextFreeDay,
+    /// Move them to today, raising their priority by one for every day they're late.
+    Overdue,
+}
+
+/// How many days from today [`Rollover::NextFreeDay`] looks for room, tasks that don't fit
+/// into any of them end up on today.
+const ROLLOVER_DAYS: usize = 28;
+
+/// How many days from its start [`Planner::add_recurring`] and [`Planner::add_static`] look
+/// for a recurring task overlapping static tasks, a year being enough for its rule to meet
+/// every other one.
+const RECURRING_CHECK_DAYS: usize = 366;
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     wake_time: NaiveTime,
     bed_time: NaiveTime,
+    #[serde(default)]
+    strategy: Strategy,
+    #[serde(default = "system_time_zone")]
+    time_zone: Tz,
+    #[serde(default)]
+    rollover: Rollover,
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "Duration::zero")]
+    buffer: Duration,
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "Duration::zero")]
+    travel: Duration,
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "Duration::zero")]
+    part_gap: Duration,
+    #[serde(default)]
+    breaks: Vec<Break>,
+    /// Waking hours of every weekday from Monday on, if not the default ones.
+    #[serde(default)]
+    weekdays: [Option<TimeRange>; 7],
+    /// Waking hours of single dates, over those of their weekday.
+    #[serde(default)]
+    dates: BTreeMap<NaiveDate, TimeRange>,
 }
 
 impl Config {
@@ -53,6 +144,319 @@ impl Config {
         Self {
             wake_time,
             bed_time,
+            strategy: Strategy::default(),
+            time_zone: system_time_zone(),
+            rollover: Rollover::default(),
+            buffer: Duration::zero(),
+            travel: Duration::zero(),
+            part_gap: Duration::zero(),
+            breaks: vec![],
+            weekdays: [None; 7],
+            dates: BTreeMap::new(),
+        }
+    }
+
+    /// Wakes up at `wake_time` and goes to bed at `bed_time` on days without waking hours of
+    /// their own.
+    pub fn day(mut self, wake_time: NaiveTime, bed_time: NaiveTime) -> Self {
+        self.wake_time = wake_time;
+        self.bed_time = bed_time;
+        self
+    }
+
+    /// Wakes up at `wake_time` and goes to bed at `bed_time` on `weekday` instead of the
+    /// default times.
+    pub fn weekday(mut self, weekday: Weekday, wake_time: NaiveTime, bed_time: NaiveTime) -> Self {
+        self.weekdays[weekday.num_days_from_monday() as usize] =
+            Some(TimeRange::new(wake_time, bed_time));
+        self
+    }
+
+    /// Sets the waking hours of Monday to Friday, see [`Config::weekday`].
+    pub fn weekdays(self, wake_time: NaiveTime, bed_time: NaiveTime) -> Self {
+        [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ]
+        .into_iter()
+        .fold(self, |config, weekday| {
+            config.weekday(weekday, wake_time, bed_time)
+        })
+    }
+
+    /// Sets the waking hours of Saturday and Sunday, see [`Config::weekday`].
+    pub fn weekends(self, wake_time: NaiveTime, bed_time: NaiveTime) -> Self {
+        self.weekday(Weekday::Sat, wake_time, bed_time)
+            .weekday(Weekday::Sun, wake_time, bed_time)
+    }
+
+    /// Wakes up at `wake_time` and goes to bed at `bed_time` on `date` only, whatever the
+    /// weekday.
+    pub fn date(mut self, date: NaiveDate, wake_time: NaiveTime, bed_time: NaiveTime) -> Self {
+        self.dates.insert(date, TimeRange::new(wake_time, bed_time));
+        self
+    }
+
+    /// Goes back to the waking hours of the weekday on `date`.
+    pub fn reset_date(mut self, date: NaiveDate) -> Self {
+        self.dates.remove(&date);
+        self
+    }
+
+    /// Goes back to the default waking hours on `weekday`.
+    pub fn reset_weekday(mut self, weekday: Weekday) -> Self {
+        self.weekdays[weekday.num_days_from_monday() as usize] = None;
+        self
+    }
+
+    /// What happens to dynamic tasks that weren't done in time, see [`Planner::advance`].
+    pub fn rollover(mut self, rollover: Rollover) -> Self {
+        self.rollover = rollover;
+        self
+    }
+
+    /// Time zone the planner's days are in, the system's by default.
+    pub fn time_zone(mut self, time_zone: Tz) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Time kept free before and after every task without a padding of its own.
+    pub fn buffer(mut self, buffer: Duration) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// Time it takes to get to a task at a different location than the task before it.
+    pub fn travel(mut self, travel: Duration) -> Self {
+        self.travel = travel;
+        self
+    }
+
+    /// Least time kept between two parts of a split task, none by default.
+    pub fn part_gap(mut self, part_gap: Duration) -> Self {
+        self.part_gap = part_gap;
+        self
+    }
+
+    /// Keeps `brk` free of flexible tasks every day, replacing a break of the same name.
+    pub fn protect(mut self, brk: Break) -> Self {
+        self.breaks
+            .retain(|other| other.get_name() != brk.get_name());
+        self.breaks.push(brk);
+        self
+    }
+
+    pub fn unprotect(mut self, name: &str) -> Self {
+        self.breaks.retain(|brk| brk.get_name() != name);
+        self
+    }
+
+    pub fn get_wake_time(&self) -> NaiveTime {
+        self.wake_time
+    }
+
+    pub fn get_bed_time(&self) -> NaiveTime {
+        self.bed_time
+    }
+
+    pub fn get_strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn get_rollover(&self) -> Rollover {
+        self.rollover
+    }
+
+    pub fn get_time_zone(&self) -> Tz {
+        self.time_zone
+    }
+
+    pub fn get_buffer(&self) -> Duration {
+        self.buffer
+    }
+
+    pub fn get_travel(&self) -> Duration {
+        self.travel
+    }
+
+    pub fn get_part_gap(&self) -> Duration {
+        self.part_gap
+    }
+
+    pub fn get_breaks(&self) -> &[Break] {
+        &self.breaks
+    }
+
+    /// Wall clock time of `instant` in [`Config::get_time_zone`].
+    pub fn local(&self, instant: DateTime<Utc>) -> NaiveDateTime {
+        instant.with_timezone(&self.time_zone).naive_local()
+    }
+
+    /// Waking hours of a day, ending on the next day if the bed time is after midnight. Days
+    /// with waking hours of their own use [`Config::get_day_on`] instead.
+    pub fn get_day(&self) -> TimeRange {
+        TimeRange::new(self.wake_time, self.bed_time)
+    }
+
+    /// Waking hours of `weekday`.
+    pub fn get_day_on_weekday(&self, weekday: Weekday) -> TimeRange {
+        self.weekdays[weekday.num_days_from_monday() as usize].unwrap_or_else(|| self.get_day())
+    }
+
+    /// Waking hours of `date`, its own or those of its weekday.
+    pub fn get_day_on(&self, date: NaiveDate) -> TimeRange {
+        match self.dates.get(&date) {
+            Some(day) => *day,
+            None => self.get_day_on_weekday(date.weekday()),
+        }
+    }
+
+    /// The dates with waking hours of their own.
+    pub fn get_dates(&self) -> &BTreeMap<NaiveDate, TimeRange> {
+        &self.dates
+    }
+
+    /// The config with the waking hours of `weekday` as the only ones, for the weekly
+    /// templates of a [`Planner`].
+    pub fn for_weekday(&self, weekday: Weekday) -> Config {
+        self.with_day(self.get_day_on_weekday(weekday))
+    }
+
+    /// The config with the waking hours of `date` as the only ones.
+    pub fn for_date(&self, date: NaiveDate) -> Config {
+        self.with_day(self.get_day_on(date))
+    }
+
+    fn with_day(&self, day: TimeRange) -> Config {
+        Config {
+            wake_time: day.start,
+            bed_time: day.end,
+            weekdays: [None; 7],
+            dates: BTreeMap::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Time kept around `task`, travel being kept for every task with a location until it's
+    /// known whether the task before it is somewhere else.
+    fn padding(&self, task: &Task) -> Padding {
+        let (before, after) = task.padding.unwrap_or((self.buffer, self.buffer));
+        Padding {
+            travel: match task.location {
+                Some(_) => self.travel,
+                None => Duration::zero(),
+            },
+            before,
+            after,
+        }
+    }
+
+    /// The day `now` belongs to. Past midnight it's still the previous day until its bed time,
+    /// if that's after midnight.
+    pub fn logical_date(&self, now: NaiveDateTime) -> NaiveDate {
+        let yesterday = now.date() - Days::new(1);
+        let day = self.get_day_on(yesterday);
+        if day.ends_next_day() && now.time() < day.end {
+            yesterday
+        } else {
+            now.date()
+        }
+    }
+
+    /// When `time` on the day `date` actually happens, times before the wake time of `date`
+    /// being after midnight.
+    pub fn anchor(&self, date: NaiveDate, time: &TimeRange) -> (NaiveDateTime, NaiveDateTime) {
+        let date = if time.start < self.get_day_on(date).start {
+            date + Days::new(1)
+        } else {
+            date
+        };
+        let start = date.and_time(time.start);
+        (start, start + time.to_duration())
+    }
+
+    /// Wall clock time of the day `date` that doesn't exist because the clocks are set forward
+    /// during it, making the day an hour (or so) shorter.
+    pub fn missing_time(&self, date: NaiveDate) -> Option<TimeRange> {
+        let (at, from, to) = self.offset_change(date)?;
+        if from >= to {
+            return None;
+        }
+
+        let missing = TimeRange::new((at + from).time(), (at + to).time());
+        missing.intersection(&self.get_day_on(date))
+    }
+
+    /// Wall clock time of the day `date` that happens twice because the clocks are set back
+    /// during it, making the day an hour (or so) longer.
+    pub fn repeated_time(&self, date: NaiveDate) -> Option<TimeRange> {
+        let (at, from, to) = self.offset_change(date)?;
+        if from <= to {
+            return None;
+        }
+
+        let repeated = TimeRange::new((at + to).time(), (at + from).time());
+        repeated.intersection(&self.get_day_on(date))
+    }
+
+    /// When during the day `date` the clocks change, in UTC, along with the offsets from UTC
+    /// before and after.
+    fn offset_change(&self, date: NaiveDate) -> Option<(NaiveDateTime, Duration, Duration)> {
+        let (start, end) = self.anchor(date, &self.get_day_on(date));
+        let (mut before, mut after) = (self.to_utc(start), self.to_utc(end));
+        let offset = |utc: NaiveDateTime| {
+            Duration::seconds(
+                self.time_zone
+                    .offset_from_utc_datetime(&utc)
+                    .fix()
+                    .local_minus_utc() as i64,
+            )
+        };
+        let (from, to) = (offset(before), offset(after));
+        if from == to {
+            return None;
+        }
+
+        // the offset changes exactly once between the two
+        while after - before > Duration::seconds(1) {
+            let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+            if offset(middle) == from {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        Some((after, from, to))
+    }
+
+    /// The instant `local` wall clock time happens at, or the first one after it if it doesn't
+    /// exist.
+    fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let mut local = local;
+        loop {
+            if let Some(time) = self.time_zone.from_local_datetime(&local).earliest() {
+                return time.naive_utc();
+            }
+            local += Duration::minutes(15);
+        }
+    }
+
+    /// How long after the wake time `time` is.
+    fn offset(&self, time: NaiveTime) -> Duration {
+        let offset = time.signed_duration_since(self.wake_time);
+        if offset < Duration::zero() {
+            offset + Duration::days(1)
+        } else {
+            offset
         }
     }
 }
@@ -60,9 +464,19 @@ impl Config {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Planner {
     config: Config,
+    /// What every week looks like, from Monday to Sunday.
     days: [Day; 7],
+    #[serde(default)]
+    calendar: BTreeMap<NaiveDate, DayPlan>,
+    #[serde(default)]
+    recurring: Vec<RecurringTask>,
     dynamic_tasks: Vec<DynamicTask>,
     dynamic_done: Vec<(DynamicTask, NaiveDateTime)>,
+    /// Original date (or deadline) and priority of tasks rolled over past it.
+    #[serde(default)]
+    overdue: BTreeMap<Uuid, (NaiveDate, i32)>,
+    #[serde(skip, default = "system_clock")]
+    clock: Box<dyn Clock>,
 }
 
 impl Planner {
@@ -78,328 +492,1073 @@ impl Planner {
                 day_creation!(),
                 day_creation!(),
             ],
+            calendar: BTreeMap::new(),
+            recurring: vec![],
             dynamic_tasks: vec![],
             dynamic_done: vec![],
+            overdue: BTreeMap::new(),
+            clock: system_clock(),
         }
     }
 
-    pub fn get_freetime_current(&self) -> Vec<Schedule> {
-        self.current_day().get_freetime(&self.config)
+    /// Uses `clock` instead of the system's clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    pub fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// The current wall clock time in [`Config::get_time_zone`].
+    pub fn now(&self) -> NaiveDateTime {
+        self.config.local(self.clock.now())
+    }
+
+    pub fn get_config(&self) -> &Config {
+        &self.config
     }
 
-    pub fn get_schedule_with_dynamics(&self) -> (Vec<Schedule>, Vec<String>) {
-        self.get_schedule_with_dynamics_nth(current()).unwrap()
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
     }
 
-    pub fn get_freetime_nth(&self, n: usize) -> Result<Vec<Schedule>, String> {
+    /// The day it currently is, see [`Config::logical_date`].
+    pub fn today(&self) -> NaiveDate {
+        self.config.logical_date(self.now())
+    }
+
+    /// Date of the `n`th day of the current week.
+    fn nth_date(&self, n: usize) -> Result<NaiveDate, LuniteError> {
         if n > 6 {
-            return Err(format!("Expected n <= 6, n is {n}"));
+            return Err(LuniteError::DayOutOfRange(n));
         }
-        Ok(self.days[n].get_freetime(&self.config))
+
+        let today = self.today();
+        Ok(today - Days::new(weekday_index(today) as u64) + Days::new(n as u64))
+    }
+
+    pub fn get_dynamic_tasks(&self) -> &[DynamicTask] {
+        &self.dynamic_tasks
+    }
+
+    pub fn get_freetime_current(&self) -> Vec<Schedule<'_>> {
+        self.get_freetime_for(self.today())
+    }
+
+    pub fn get_schedule_with_dynamics(&self) -> (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>) {
+        self.schedule_for(self.today())
+    }
+
+    pub fn get_freetime_nth(&self, n: usize) -> Result<Vec<Schedule<'_>>, LuniteError> {
+        Ok(self.get_freetime_for(self.nth_date(n)?))
     }
 
     pub fn get_schedule_with_dynamics_nth(
         &self,
         n: usize,
-    ) -> Result<(Vec<Schedule>, Vec<String>), String> {
-        let mut freetime = self.get_freetime_nth(n)?;
-        let dynamic_tasks = self
-            .current_day()
+    ) -> Result<(Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>), LuniteError> {
+        Ok(self.schedule_for(self.nth_date(n)?))
+    }
+
+    /// Like [`Planner::get_schedule_with_dynamics_nth`], but places the dynamic tasks with
+    /// `scheduler` instead of the one set in [`Config`].
+    pub fn get_schedule_with_scheduler_nth(
+        &self,
+        n: usize,
+        scheduler: &dyn Scheduler,
+    ) -> Result<(Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>), LuniteError> {
+        Ok(self.schedule_with_scheduler_for(self.nth_date(n)?, scheduler))
+    }
+
+    /// Free time of `date` around [`Planner::statics_on`] it, without
+    /// [`Config::missing_time`] and with [`Config::repeated_time`] in it twice.
+    pub fn get_freetime_for(&self, date: NaiveDate) -> Vec<Schedule<'_>> {
+        settle(self.reserved_freetime(date))
+    }
+
+    /// Free time of `date` with travel kept before every static task with a location, for the
+    /// dynamic tasks to be placed around. Breaks make way for the fixed dynamic tasks.
+    fn reserved_freetime(&self, date: NaiveDate) -> Vec<Schedule<'_>> {
+        let config = self.config.for_date(date);
+        let statics = self
+            .statics_on(date)
+            .into_iter()
+            .filter(|task| !self.is_static_done(date, &task.task.uuid))
+            .collect();
+        let mut free = freetime(&config, statics);
+        if let Some(missing) = config.missing_time(date) {
+            free = without_missing(&config, free, missing);
+        }
+        if let Some(repeated) = config.repeated_time(date) {
+            free = with_repeated(free, repeated);
+        }
+
+        let fixed = self
+            .dynamic_tasks
+            .iter()
+            .filter_map(|task| match task {
+                DynamicTask::Fixed {
+                    task, date: other, ..
+                } if *other == date => Some(task.time),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        breaks::protect(&config, &self.config.breaks, free, &fixed)
+    }
+
+    /// Static tasks happening on `date`, those of its weekday that aren't skipped and the
+    /// recurring tasks repeating on it, ordered by start time.
+    pub fn statics_on(&self, date: NaiveDate) -> Vec<&StaticTask> {
+        let skipped = self
+            .calendar
+            .get(&date)
+            .map(|plan| plan.skipped.as_slice())
+            .unwrap_or_default();
+        let day = &self.days[weekday_index(date)];
+
+        let mut statics = day
+            .static_tasks
+            .iter()
+            .filter(|task| !skipped.contains(&task.task.uuid))
+            .chain(
+                self.recurring
+                    .iter()
+                    .filter(|task| task.get_recurrence().occurs_on(date))
+                    .map(RecurringTask::get_task),
+            )
+            .collect::<Vec<_>>();
+        statics.sort();
+        statics
+    }
+
+    pub fn schedule_for(&self, date: NaiveDate) -> (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>) {
+        self.schedule_with_scheduler_for(date, &self.config.strategy)
+    }
+
+    /// Schedules of all dates from `from` to `to`, both included.
+    pub fn schedule_range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> BTreeMap<NaiveDate, (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>)> {
+        let plan = self.deadline_plan();
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| (date, self.schedule_planned(date, &plan)))
+            .collect()
+    }
+
+    /// Like [`Planner::schedule_for`], but with the dates of tasks with a deadline taken from
+    /// `plan` instead of being picked again.
+    pub fn schedule_planned(
+        &self,
+        date: NaiveDate,
+        plan: &DeadlinePlan,
+    ) -> (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>) {
+        self.schedule_planned_with(date, plan, &self.config.strategy)
+    }
+
+    /// Like [`Planner::schedule_for`], but places the dynamic tasks with `scheduler` instead of
+    /// the one set in [`Config`].
+    pub fn schedule_with_scheduler_for(
+        &self,
+        date: NaiveDate,
+        scheduler: &dyn Scheduler,
+    ) -> (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>) {
+        self.schedule_planned_with(date, &self.plan_with(scheduler), scheduler)
+    }
+
+    fn schedule_planned_with(
+        &self,
+        date: NaiveDate,
+        plan: &DeadlinePlan,
+        scheduler: &dyn Scheduler,
+    ) -> (Vec<Schedule<'_>>, Vec<ScheduleDiagnostic>) {
+        self.schedule_tasks(date, self.planned_on(date, plan), scheduler)
+    }
+
+    /// Dynamic tasks on `date` when the ones with a deadline are on the dates of `plan`.
+    fn planned_on(&self, date: NaiveDate, plan: &DeadlinePlan) -> Vec<&DynamicTask> {
+        self.dynamic_tasks
+            .iter()
+            .filter(|task| plan.date_of(task) == Some(date))
+            .collect()
+    }
+
+    /// Places `tasks` into the free time of `date`, preempting lower priority tasks if they
+    /// don't all fit.
+    fn schedule_tasks<'a>(
+        &'a self,
+        date: NaiveDate,
+        mut tasks: Vec<&'a DynamicTask>,
+        scheduler: &dyn Scheduler,
+    ) -> (Vec<Schedule<'a>>, Vec<ScheduleDiagnostic>) {
+        tasks.sort_by_key(placement_order);
+
+        let Placement {
+            mut schedule,
+            diagnostics: mut errors,
+            mut unplaced,
+        } = self.place(date, &tasks, scheduler);
+        let mut preempted = vec![];
+
+        // while a task doesn't fit, make room for it by taking out the lowest priority task
+        // that did
+        while let Some(top) = unplaced.iter().map(|task| task.get_priority()).max() {
+            let victim = tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| {
+                    task.get_priority() < top && !unplaced.iter().any(|t| std::ptr::eq(*t, **task))
+                })
+                .min_by_key(|(i, task)| (task.get_priority(), Reverse(*i)))
+                .map(|(i, _)| i);
+            let Some(victim) = victim else {
+                break;
+            };
+
+            preempted.push(tasks.remove(victim));
+            Placement {
+                schedule,
+                diagnostics: errors,
+                unplaced,
+            } = self.place(date, &tasks, scheduler);
+        }
+
+        // preempted tasks get another chance in case there's room left after all
+        preempted.sort_by_key(|task| Reverse(task.get_priority()));
+        let mut preempted_errors = vec![];
+        for task in preempted {
+            let mut with_task = tasks.clone();
+            with_task.push(task);
+            with_task.sort_by_key(placement_order);
+
+            let placement = self.place(date, &with_task, scheduler);
+            if placement
+                .unplaced
+                .iter()
+                .all(|t| unplaced.iter().any(|u| std::ptr::eq(*t, *u)))
+            {
+                tasks = with_task;
+                Placement {
+                    schedule,
+                    diagnostics: errors,
+                    unplaced,
+                } = placement;
+            } else {
+                preempted_errors.push(ScheduleDiagnostic::Preempted {
+                    task_uuid: task.get_task().uuid,
+                });
+            }
+        }
+        errors.append(&mut preempted_errors);
+
+        (settle(schedule), errors)
+    }
+
+    /// Places `tasks` into the free time of `date` with `scheduler`, with the breaks repeating
+    /// after work put between them wherever they end up packed together for too long.
+    fn place<'a>(
+        &'a self,
+        date: NaiveDate,
+        tasks: &[&'a DynamicTask],
+        scheduler: &dyn Scheduler,
+    ) -> Placement<'a> {
+        let mut placement = scheduler.schedule(&self.config, self.reserved_freetime(date), tasks);
+        placement.schedule = breaks::after_work(
+            &self.config.for_date(date),
+            &self.config.breaks,
+            placement.schedule,
+        );
+        placement
+    }
+
+    /// Dynamic tasks on `date` along with their indices into [`Planner::get_dynamic_tasks`].
+    /// Tasks with a deadline are on the date the planner picked for them.
+    pub fn dynamic_tasks_on(&self, date: NaiveDate) -> Vec<(usize, &DynamicTask)> {
+        let plan = self.deadline_plan();
+        self.dynamic_tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| plan.date_of(task) == Some(date))
+            .collect()
+    }
+
+    /// Tasks with a deadline that don't fit into any day before it, or whose deadline has
+    /// passed.
+    pub fn at_risk(&self) -> Vec<&DynamicTask> {
+        self.at_risk_in(&self.deadline_plan())
+    }
+
+    /// Like [`Planner::at_risk`], but with the tasks at risk taken from `plan`. Tasks removed
+    /// since it was made are left out.
+    pub fn at_risk_in(&self, plan: &DeadlinePlan) -> Vec<&DynamicTask> {
+        plan.at_risk
+            .iter()
+            .filter_map(|uuid| {
+                self.dynamic_tasks
+                    .iter()
+                    .find(|task| task.get_task().uuid == *uuid)
+            })
+            .collect()
+    }
+
+    /// Picks the dates of tasks with a deadline, earliest deadline first. Every task goes on
+    /// the first day from today on it fits into without pushing out any other task.
+    ///
+    /// Picking them means scheduling the days up to the deadlines, so when scheduling more than
+    /// a single date, the plan can be made once and passed to [`Planner::schedule_planned`].
+    pub fn deadline_plan(&self) -> DeadlinePlan {
+        self.plan_with(&self.config.strategy)
+    }
+
+    fn plan_with(&self, scheduler: &dyn Scheduler) -> DeadlinePlan {
+        let today = self.today();
+        let mut plan = DeadlinePlan::default();
+
+        let mut with_deadline = self
+            .dynamic_tasks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, task)| Some((i, task, *task.get_deadline()?)))
+            .collect::<Vec<_>>();
+        with_deadline
+            .sort_by_key(|(i, task, deadline)| (*deadline, Reverse(task.get_priority()), *i));
+
+        let mut tried = Tried::new();
+        for (_, task, deadline) in with_deadline {
+            let date = (*task.get_date())
+                .max(today)
+                .iter_days()
+                .take_while(|date| *date <= deadline)
+                .find(|&date| self.fits_on(date, task, &plan, scheduler, &mut tried));
+
+            match date {
+                Some(date) => {
+                    plan.dates.insert(task.get_task().uuid, date);
+                }
+                None => plan.at_risk.push(task.get_task().uuid),
+            }
+        }
+
+        plan
+    }
+
+    /// Uuids of `tasks` missing from the schedule of `date`, scheduled only the first time
+    /// `tried` sees them on it.
+    fn unplaced_on(
+        &self,
+        date: NaiveDate,
+        tasks: Vec<&DynamicTask>,
+        scheduler: &dyn Scheduler,
+        tried: &mut Tried,
+    ) -> Vec<Uuid> {
+        let mut uuids = tasks
+            .iter()
+            .map(|task| task.get_task().uuid)
+            .collect::<Vec<_>>();
+        uuids.sort();
+        tried
+            .entry((date, uuids))
+            .or_insert_with(|| {
+                let (_, diagnostics) = self.schedule_tasks(date, tasks, scheduler);
+                unplaced(&diagnostics).copied().collect()
+            })
+            .clone()
+    }
+
+    /// Whether `task` can be added to the tasks `plan` puts on `date` without pushing out any
+    /// of them.
+    fn fits_on(
+        &self,
+        date: NaiveDate,
+        task: &DynamicTask,
+        plan: &DeadlinePlan,
+        scheduler: &dyn Scheduler,
+        tried: &mut Tried,
+    ) -> bool {
+        let mut tasks = self.planned_on(date, plan);
+        let before = self.unplaced_on(date, tasks.clone(), scheduler, tried);
+        tasks.push(task);
+        let after = self.unplaced_on(date, tasks, scheduler, tried);
+        after.iter().all(|uuid| before.contains(uuid))
+    }
+
+    /// Applies the [`Rollover`] of the config to the dynamic tasks whose date, or deadline if
+    /// they have one, has passed. Returns whether any task changed, which they never do with
+    /// the default [`Rollover::Keep`].
+    pub fn advance(&mut self) -> bool {
+        if self.config.rollover == Rollover::Keep {
+            return false;
+        }
+        let today = self.today();
+        let late = self
             .dynamic_tasks
             .iter()
-            .map(|i| &self.dynamic_tasks[*i])
-            .collect::<Vec<&DynamicTask>>();
-        let mut errors = vec![];
+            .enumerate()
+            .filter(|(_, task)| task.get_deadline().unwrap_or(task.get_date()) < &today)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if late.is_empty() {
+            return false;
+        }
+
+        for &i in late.iter().rev() {
+            let task = &self.dynamic_tasks[i];
+            let uuid = task.get_task().uuid;
+            let (due, priority) = *self.overdue.entry(uuid).or_insert((
+                *task.get_deadline().unwrap_or(task.get_date()),
+                task.get_priority(),
+            ));
+
+            let date = match self.config.rollover {
+                Rollover::Drop => {
+                    self.dynamic_tasks.remove(i);
+                    self.overdue.remove(&uuid);
+                    continue;
+                }
+                Rollover::NextFreeDay => {
+                    let plan = self.deadline_plan();
+                    let mut tried = Tried::new();
+                    today
+                        .iter_days()
+                        .take(ROLLOVER_DAYS)
+                        .find(|&date| {
+                            self.fits_on(date, task, &plan, &self.config.strategy, &mut tried)
+                        })
+                        .unwrap_or(today)
+                }
+                Rollover::Overdue => today,
+                Rollover::Keep => continue,
+            };
 
-        for task in dynamic_tasks {
+            let task = &mut self.dynamic_tasks[i];
             match task {
-                DynamicTask::Fixed {
-                    task: StaticTask { time, .. },
+                DynamicTask::Flexible {
+                    date: old,
+                    deadline,
                     ..
                 } => {
-                    for i in 0..freetime.len() {
-                        if let Schedule::Free(range) = &freetime[i] {
-                            if time.subset(range) {
-                                let first = if time.start != range.start {
-                                    Some(TimeRange::new(range.start, time.start))
-                                } else {
-                                    None
-                                };
-                                let second = if time.end != range.end {
-                                    Some(TimeRange::new(time.end, range.end))
-                                } else {
-                                    None
-                                };
-
-                                freetime[i] = Schedule::DynamicTask(task);
-                                let mut ii = i;
-                                if let Some(range) = first {
-                                    freetime.insert(i, Schedule::Free(range));
-                                    ii += 1;
-                                }
-                                if let Some(range) = second {
-                                    freetime.insert(ii + 1, Schedule::Free(range))
-                                }
-                                break;
-                            }
-                        }
-                    }
+                    *old = date;
+                    *deadline = None;
                 }
-                DynamicTask::Flexible {
-                    length, can_split, ..
-                } => {
-                    if *can_split {
-                        let duration = freetime
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(i, schedule)| {
-                                if let Schedule::Free(range) = schedule {
-                                    Some((i, range))
-                                } else {
-                                    None
-                                }
-                            })
-                            .fold((vec![], Duration::seconds(0)), |mut duration, range| {
-                                duration.0.push(range.0);
-                                (duration.0, duration.1 + range.1.to_duration())
-                            });
-                        if duration.1 >= *length {
-                            let mut length = length.to_owned();
-                            let mut part = 1;
-
-                            for i in duration.0 {
-                                if let Schedule::Free(range) = &freetime[i] {
-                                    let free_range = *range;
-                                    let duration = range.to_duration();
-                                    if duration == length {
-                                        freetime[i] = Schedule::DynamicPart(
-                                            task.fixed_split(&length, part).unwrap(),
-                                        );
-                                        break;
-                                    } else if duration > length {
-                                        freetime[i] = Schedule::DynamicPart(
-                                            task.fixed_split(&length, part).unwrap(),
-                                        );
-                                        freetime.insert(
-                                            i + 1,
-                                            Schedule::Free(TimeRange::new(
-                                                free_range.start + length,
-                                                free_range.end,
-                                            )),
-                                        );
-
-                                        break;
-                                    } else {
-                                        freetime[i] = Schedule::DynamicPart(
-                                            task.fixed_split(&duration, part).unwrap(),
-                                        );
-                                        length = length - duration;
-                                        part += 1;
-                                    }
-                                } else {
-                                    unreachable!()
-                                }
-                            }
-                        } else {
-                            errors.push(format!(
-                                "There isn't enough freetime for {task:#?}; total freetime {}",
-                                duration.1.num_seconds()
-                            ))
-                        }
-
-                        continue;
-                    }
-
-                    for i in 0..freetime.len() {
-                        if let Schedule::Free(range) = &freetime[i] {
-                            let range = range.to_owned();
-                            let duration = range.to_duration();
-                            if duration >= *length {
-                                freetime[i] = Schedule::DynamicTask(task);
-
-                                if duration != *length {
-                                    freetime.insert(
-                                        i + 1,
-                                        Schedule::Free(TimeRange::new(
-                                            range.start + *length,
-                                            range.end,
-                                        )),
-                                    )
-                                }
-                                break;
-                            }
-                        }
-                    }
+                DynamicTask::Fixed { date: old, .. } => *old = date,
+            }
+            if self.config.rollover == Rollover::Overdue {
+                let late = (today - due).num_days() as i32;
+                match task {
+                    DynamicTask::Flexible { priority: old, .. }
+                    | DynamicTask::Fixed { priority: old, .. } => *old = priority + late,
                 }
-            };
+            }
         }
+        self.dynamic_tasks.sort();
 
-        Ok((freetime, errors))
+        true
+    }
+
+    /// Dynamic tasks that weren't done by their date, or deadline, along with it. Those are
+    /// the tasks [`Planner::advance`] rolled over and the ones still before today.
+    pub fn overdue(&self) -> Vec<(&DynamicTask, NaiveDate)> {
+        let today = self.today();
+        self.dynamic_tasks
+            .iter()
+            .filter_map(|task| match self.overdue.get(&task.get_task().uuid) {
+                Some((due, _)) => Some((task, *due)),
+                None => {
+                    let due = *task.get_deadline().unwrap_or(task.get_date());
+                    (due < today).then_some((task, due))
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_day_plan(&self, date: NaiveDate) -> Option<&DayPlan> {
+        self.calendar.get(&date)
+    }
+
+    /// Leaves the `task`th static task of the weekday out on `date` only.
+    pub fn skip_static(&mut self, date: NaiveDate, task: usize) -> Result<(), LuniteError> {
+        let uuid = self.days[weekday_index(date)]
+            .static_tasks
+            .get(task)
+            .ok_or(LuniteError::TaskNotFound(task))?
+            .task
+            .uuid;
+
+        let plan = self.calendar.entry(date).or_default();
+        if !plan.skipped.contains(&uuid) {
+            plan.skipped.push(uuid);
+        }
+        Ok(())
     }
 
     pub fn current_day(&self) -> &Day {
-        &self.days[current()]
+        &self.days[weekday_index(self.today())]
     }
 
-    pub fn nth_day(&self, nth: usize) -> Result<&Day, String> {
+    pub fn nth_day(&self, nth: usize) -> Result<&Day, LuniteError> {
         if nth > 6 {
-            return Err(format!("Expected n <= 6, n is {nth}"));
+            return Err(LuniteError::DayOutOfRange(nth));
         }
         Ok(&self.days[nth])
     }
 
     pub fn current_day_mut(&mut self) -> &mut Day {
-        &mut self.days[current()]
+        let today = weekday_index(self.today());
+        &mut self.days[today]
     }
 
-    pub fn nth_day_mut(&mut self, nth: usize) -> Result<&mut Day, String> {
+    pub fn nth_day_mut(&mut self, nth: usize) -> Result<&mut Day, LuniteError> {
         if nth > 6 {
-            return Err(format!("Expected n <= 6, n is {nth}"));
+            return Err(LuniteError::DayOutOfRange(nth));
         }
         Ok(&mut self.days[nth])
     }
 
-    pub fn add_dynamic(&mut self, task: DynamicTask) -> Result<(), String> {
-        if task.get_date() < &Local::now().date_naive() {
-            return Err(String::from("Task can't start in the past"));
+    /// Adds a static task to the `nth` day of the week, see [`Day::add_static`]. It can't
+    /// overlap the recurring tasks happening on that weekday either, checked for
+    /// [`RECURRING_CHECK_DAYS`] days from their start.
+    pub fn add_static(&mut self, nth: usize, task: StaticTask) -> Result<(), LuniteError> {
+        if nth > 6 {
+            return Err(LuniteError::DayOutOfRange(nth));
+        }
+        let weekday = nth_weekday(nth);
+        if let Some(clash) = self.recurring_clash(weekday, &task.time) {
+            return Err(LuniteError::Conflict {
+                task_uuid: clash.task.uuid,
+                name: clash.task.name.clone(),
+                time: clash.time,
+            });
+        }
+
+        self.days[nth].add_static(task, &self.config.for_weekday(weekday))
+    }
+
+    /// A recurring task happening on `weekday` at a time overlapping `time`, checked for
+    /// [`RECURRING_CHECK_DAYS`] days from its start.
+    pub(crate) fn recurring_clash(
+        &self,
+        weekday: Weekday,
+        time: &TimeRange,
+    ) -> Option<&StaticTask> {
+        self.recurring
+            .iter()
+            .find(|recurring| {
+                let recurrence = recurring.get_recurrence();
+                recurring.get_task().time.overlap(time)
+                    && recurrence
+                        .get_start()
+                        .iter_days()
+                        .take(RECURRING_CHECK_DAYS)
+                        .any(|date| date.weekday() == weekday && recurrence.occurs_on(date))
+            })
+            .map(RecurringTask::get_task)
+    }
+
+    /// Adds a static task to the `nth` day of the week, see [`Day::add_static_allow_overlap`].
+    pub fn add_static_allow_overlap(
+        &mut self,
+        nth: usize,
+        task: StaticTask,
+    ) -> Result<(), LuniteError> {
+        if nth > 6 {
+            return Err(LuniteError::DayOutOfRange(nth));
+        }
+        let config = self.config.for_weekday(nth_weekday(nth));
+        self.days[nth].add_static_allow_overlap(task, &config)
+    }
+
+    pub fn get_recurring(&self) -> &[RecurringTask] {
+        &self.recurring
+    }
+
+    /// Adds a task repeating on the dates of its recurrence. Like [`Planner::add_static`] it
+    /// can't overlap any of [`Planner::statics_on`] the dates it happens on, checked for
+    /// [`RECURRING_CHECK_DAYS`] days from its start.
+    pub fn add_recurring(&mut self, task: RecurringTask) -> Result<(), LuniteError> {
+        let recurrence = task.get_recurrence();
+        let time = task.get_task().time;
+        let clash = recurrence
+            .get_start()
+            .iter_days()
+            .take(RECURRING_CHECK_DAYS)
+            .filter(|date| recurrence.occurs_on(*date))
+            .find_map(|date| {
+                self.statics_on(date)
+                    .into_iter()
+                    .find(|other| other.time.overlap(&time))
+            });
+        if let Some(clash) = clash {
+            return Err(LuniteError::Conflict {
+                task_uuid: clash.task.uuid,
+                name: clash.task.name.clone(),
+                time: clash.time,
+            });
+        }
+
+        self.add_recurring_allow_overlap(task)
+    }
+
+    /// Like [`Planner::add_recurring`], but lets the task overlap other static tasks, it's only
+    /// checked against the waking hours of the dates it happens on.
+    pub fn add_recurring_allow_overlap(&mut self, task: RecurringTask) -> Result<(), LuniteError> {
+        let recurrence = task.get_recurrence();
+        let rule = recurrence.get_rule();
+        if !rule.is_valid() {
+            return Err(LuniteError::InvalidRule(*rule));
+        }
+        let time = task.get_task().time;
+        let outside = recurrence
+            .get_start()
+            .iter_days()
+            .take(RECURRING_CHECK_DAYS)
+            .filter(|date| recurrence.occurs_on(*date))
+            .map(|date| self.config.get_day_on(date))
+            .find(|day| time.start == time.end || !time.subset(day));
+        if let Some(day) = outside {
+            return Err(LuniteError::OutsideOfDay { time, day });
+        }
+
+        self.recurring.push(task);
+        Ok(())
+    }
+
+    /// Leaves the `task`th recurring task out on `date`.
+    pub fn skip_recurring(&mut self, date: NaiveDate, task: usize) -> Result<(), LuniteError> {
+        self.recurring
+            .get_mut(task)
+            .ok_or(LuniteError::TaskNotFound(task))?
+            .recurrence
+            .add_exception(date);
+        Ok(())
+    }
+
+    pub fn add_dynamic(&mut self, task: DynamicTask) -> Result<(), LuniteError> {
+        let last = task.get_deadline().unwrap_or(task.get_date());
+        if last < &self.today() {
+            return Err(LuniteError::DateInPast(*last));
         }
 
         self.dynamic_tasks.push(task);
         self.dynamic_tasks.sort();
-        self.update_dynamics();
         Ok(())
     }
 
-    pub fn complete_dynamic(&mut self, task: usize) -> Result<(), String> {
-        if self.current_day_mut().dynamic_tasks.contains(&task) {
-            let dynamic_id = self.current_day_mut().dynamic_tasks.swap_remove(task);
-            let dynamic_task = self.dynamic_tasks.remove(dynamic_id);
+    /// Completes the `task`th of [`Planner::statics_on`] `date`, only for that date.
+    pub fn complete_static(&mut self, date: NaiveDate, task: usize) -> Result<(), LuniteError> {
+        let uuid = self
+            .statics_on(date)
+            .get(task)
+            .ok_or(LuniteError::TaskNotFound(task))?
+            .task
+            .uuid;
+        let now = self.now();
 
-            self.dynamic_done
-                .push((dynamic_task, Local::now().naive_local()));
-            self.update_dynamics();
-
-            Ok(())
-        } else {
-            Err(format!(
-                "Expected task to be in current day dynamic tasks, got {:#?}",
-                self.current_day().dynamic_tasks
-            ))
+        let plan = self.calendar.entry(date).or_default();
+        if !plan.is_done(&uuid) {
+            plan.done.push((uuid, now));
         }
+        Ok(())
     }
 
-    fn update_dynamics(&mut self) {
-        let mut current_date = Local::now().date_naive();
+    pub fn is_static_done(&self, date: NaiveDate, uuid: &Uuid) -> bool {
+        self.calendar
+            .get(&date)
+            .is_some_and(|plan| plan.is_done(uuid))
+    }
 
-        for n in current()..7 {
-            self.days[n].dynamic_tasks = self
-                .dynamic_tasks
-                .iter()
-                .enumerate()
-                .filter_map(|(i, task)| {
-                    if task.get_date() == &current_date {
-                        Some(i)
-                    } else {
-                        None
-                    }
+    /// Static and dynamic tasks done on the dates from `from` to `to`, both included, in the
+    /// order they were done in.
+    pub fn completions_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<Completion<'_>> {
+        let statics = self.calendar.range(from..=to).flat_map(|(date, plan)| {
+            plan.done.iter().filter_map(|(uuid, done_at)| {
+                Some(Completion {
+                    date: *date,
+                    task: &self.find_static(uuid)?.task,
+                    done_at: *done_at,
                 })
-                .collect();
-            current_date = current_date.checked_add_days(Days::new(1)).unwrap();
+            })
+        });
+        let dynamics = self
+            .dynamic_done
+            .iter()
+            .filter(|(task, _)| (from..=to).contains(task.get_date()))
+            .map(|(task, done_at)| Completion {
+                date: *task.get_date(),
+                task: task.get_task(),
+                done_at: *done_at,
+            });
+
+        let mut completions = statics.chain(dynamics).collect::<Vec<_>>();
+        completions.sort_by_key(|completion| completion.done_at);
+        completions
+    }
+
+    /// The weekly or recurring static task `uuid`.
+    fn find_static(&self, uuid: &Uuid) -> Option<&StaticTask> {
+        self.days
+            .iter()
+            .flat_map(|day| &day.static_tasks)
+            .chain(self.recurring.iter().map(RecurringTask::get_task))
+            .find(|task| &task.task.uuid == uuid)
+    }
+
+    /// Completes the `task`th of [`Planner::get_dynamic_tasks`].
+    pub fn complete_dynamic(&mut self, task: usize) -> Result<(), LuniteError> {
+        if task >= self.dynamic_tasks.len() {
+            return Err(LuniteError::TaskNotFound(task));
         }
+
+        let dynamic_task = self.dynamic_tasks.remove(task);
+        self.overdue.remove(&dynamic_task.get_task().uuid);
+        let now = self.now();
+        self.dynamic_done.push((dynamic_task, now));
+        Ok(())
     }
 }
 
+/// What's different about a specific date compared to the [`Day`] of its weekday.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DayPlan {
+    /// Static tasks of the weekday that don't happen on this date.
+    #[serde(default)]
+    skipped: Vec<Uuid>,
+    /// Static tasks done on this date and when.
+    #[serde(default)]
+    done: Vec<(Uuid, NaiveDateTime)>,
+}
+
+impl DayPlan {
+    pub fn get_skipped(&self) -> &[Uuid] {
+        &self.skipped
+    }
+
+    pub fn get_done(&self) -> &[(Uuid, NaiveDateTime)] {
+        &self.done
+    }
+
+    pub fn is_done(&self, uuid: &Uuid) -> bool {
+        self.done.iter().any(|(done, _)| done == uuid)
+    }
+}
+
+/// A task done on `date`, see [`Planner::completions_between`].
+#[derive(Debug)]
+pub struct Completion<'a> {
+    pub date: NaiveDate,
+    pub task: &'a Task,
+    pub done_at: NaiveDateTime,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Day {
     static_tasks: Vec<StaticTask>,
-    static_done: Vec<(Uuid, NaiveDateTime)>,
-    dynamic_tasks: Vec<usize>,
 }
 
 impl Day {
-    pub fn add_static(&mut self, task: StaticTask) {
-        self.static_tasks.push(task);
-        self.static_tasks.sort();
+    pub fn get_static_tasks(&self) -> &[StaticTask] {
+        &self.static_tasks
     }
 
-    pub fn complete_static(&mut self, task: usize) -> Result<(), String> {
-        if self.static_tasks.len() > task {
-            let StaticTask {
-                task: Task { uuid, .. },
-                ..
-            } = self.static_tasks[task];
-            self.static_done.push((uuid, Local::now().naive_local()));
+    /// Adds a task repeating every week on this day. The task has to be within the day set by
+    /// `config` and can't overlap any other static task of the day.
+    pub fn add_static(&mut self, task: StaticTask, config: &Config) -> Result<(), LuniteError> {
+        if let Some(clash) = self
+            .static_tasks
+            .iter()
+            .find(|other| other.time.overlap(&task.time))
+        {
+            return Err(LuniteError::Conflict {
+                task_uuid: clash.task.uuid,
+                name: clash.task.name.clone(),
+                time: clash.time,
+            });
+        }
 
-            Ok(())
-        } else {
-            Err(format!("There is no task at {task}"))
+        self.add_static_allow_overlap(task, config)
+    }
+
+    /// Like [`Day::add_static`], but lets the task overlap other static tasks.
+    pub fn add_static_allow_overlap(
+        &mut self,
+        task: StaticTask,
+        config: &Config,
+    ) -> Result<(), LuniteError> {
+        let day = config.get_day();
+        if task.time.start == task.time.end || !task.time.subset(&day) {
+            return Err(LuniteError::OutsideOfDay {
+                time: task.time,
+                day,
+            });
         }
+
+        self.static_tasks.push(task);
+        self.static_tasks.sort();
+        Ok(())
     }
 
-    pub fn get_freetime(&self, config: &Config) -> Vec<Schedule> {
-        let times = self
-            .static_tasks
-            .iter()
-            .filter_map(|task| {
-                let StaticTask {
-                    task: Task { uuid, .. },
-                    ..
-                } = &task;
-                if self.static_done.iter().all(|t| &t.0 != uuid) {
-                    Some((&task.time, task))
-                } else {
-                    None
+    /// Free time of the day around its static tasks, `config` being the one of its weekday
+    /// from [`Config::for_weekday`].
+    pub fn get_freetime<'a>(&'a self, config: &'a Config) -> Vec<Schedule<'a>> {
+        let free = freetime(config, self.static_tasks.iter().collect());
+        settle(breaks::protect(config, &config.breaks, free, &[]))
+    }
+}
+
+/// `free` without the `missing` time of a day the clocks are set forward on.
+fn without_missing<'a>(
+    config: &Config,
+    free: Vec<Schedule<'a>>,
+    missing: TimeRange,
+) -> Vec<Schedule<'a>> {
+    free.into_iter()
+        .flat_map(|schedule| match schedule {
+            Schedule::Free(range) if range.overlap(&missing) => {
+                let start = config.offset(range.start);
+                let end = start + range.to_duration();
+                let missing_start = config.offset(missing.start);
+                let missing_end = missing_start + missing.to_duration();
+
+                let mut left = vec![];
+                if missing_start > start {
+                    left.push(Schedule::Free(TimeRange::new(range.start, missing.start)));
                 }
-            })
-            .collect::<Vec<(&TimeRange, &StaticTask)>>();
-        let times_len = times.len();
-        let mut free = vec![];
-
-        for i in 0..times_len {
-            if i == 0 {
-                if config.wake_time != times[i].0.start {
-                    free.push(Schedule::Free(TimeRange::new(
-                        config.wake_time,
-                        times[i].0.start,
-                    )))
+                if missing_end < end {
+                    left.push(Schedule::Free(TimeRange::new(missing.end, range.end)));
                 }
-                free.push(Schedule::Static(times[i].1));
-                continue;
+                left
             }
+            schedule => vec![schedule],
+        })
+        .collect()
+}
+
+/// `free` with the free part of the `repeated` time of a day the clocks are set back on
+/// happening a second time, right after its first time.
+fn with_repeated(free: Vec<Schedule>, repeated: TimeRange) -> Vec<Schedule> {
+    free.into_iter()
+        .flat_map(|schedule| match schedule {
+            Schedule::Free(range) => match range.intersection(&repeated) {
+                Some(again) => [
+                    TimeRange::new(range.start, again.end),
+                    again,
+                    TimeRange::new(again.end, range.end),
+                ]
+                .into_iter()
+                .filter(|range| range.start != range.end)
+                .map(Schedule::Free)
+                .collect(),
+                None => vec![Schedule::Free(range)],
+            },
+            schedule => vec![schedule],
+        })
+        .collect()
+}
+
+/// Free time between wake and bed time around `statics`. Parts of tasks outside of the day
+/// are left out, tasks entirely outside of it aren't in the schedule at all.
+fn freetime<'a>(config: &Config, statics: Vec<&'a StaticTask>) -> Vec<Schedule<'a>> {
+    let day = config.get_day();
+    let busy = statics
+        .iter()
+        .map(|task| task.time)
+        .collect::<TimeRangeSet>();
+    // a task reaching into the day from before the wake time starts with the day, it ends
+    // either a day after or on the day it starts on
+    let mut tasks = statics
+        .into_iter()
+        .filter(|task| task.time.overlap(&day))
+        .map(|task| {
+            let start = config.offset(task.time.start);
+            let end = start + task.time.to_duration();
+            let start = if start < day.to_duration() && end <= Duration::days(1) {
+                start
+            } else {
+                Duration::zero()
+            };
+            let ends = [end - Duration::days(1), end];
+            (task, config.padding(&task.task), start, ends)
+        })
+        .collect::<Vec<_>>();
+    tasks.sort_by_key(|(_, _, start, _)| *start);
 
-            if times[i - 1].0.end != times[i].0.start {
-                free.push(Schedule::Free(TimeRange::new(
-                    times[i - 1].0.end,
-                    times[i].0.start,
-                )));
+    let mut free = vec![];
+    let mut next = 0;
+    // ends of the tasks so far, with the buffers after them
+    let mut ends = vec![];
+    for gap in busy.gaps(&day) {
+        let from = config.offset(gap.start);
+        let to = from + gap.to_duration();
+        while let Some((task, padding, _, task_ends)) =
+            tasks.get(next).filter(|(_, _, start, _)| *start < from)
+        {
+            free.push(Schedule::Static(task));
+            ends.extend(task_ends.map(|end| (end, end + padding.after)));
+            next += 1;
+        }
+
+        // the buffers after tasks only take up what the next task leaves free
+        let buffer_until = ends
+            .iter()
+            .filter(|(end, _)| *end <= from)
+            .map(|(_, until)| *until)
+            .fold(from, Duration::max)
+            .min(to);
+        let (before, travel) = tasks[next..]
+            .iter()
+            .take_while(|(_, _, start, _)| *start == to)
+            .fold(
+                (Duration::zero(), Duration::zero()),
+                |(before, travel), (_, padding, ..)| {
+                    (before.max(padding.before), travel.max(padding.travel))
+                },
+            );
+        let buffer_from = (to - before).max(buffer_until);
+        let travel_from = (buffer_from - travel).max(buffer_until);
+
+        push_span(&mut free, config, Schedule::Buffer, from, buffer_until);
+        push_span(&mut free, config, Schedule::Free, buffer_until, travel_from);
+        push_span(
+            &mut free,
+            config,
+            Schedule::Travel,
+            travel_from,
+            buffer_from,
+        );
+        push_span(&mut free, config, Schedule::Buffer, buffer_from, to);
+    }
+    free.extend(
+        tasks[next..]
+            .iter()
+            .map(|(task, ..)| Schedule::Static(task)),
+    );
+
+    free
+}
+
+/// Pushes a `kind` entry from `from` to `to` after the wake time, if there's time between them.
+fn push_span<'a>(
+    schedule: &mut Vec<Schedule<'a>>,
+    config: &Config,
+    kind: fn(TimeRange) -> Schedule<'a>,
+    from: Duration,
+    to: Duration,
+) {
+    if from < to {
+        schedule.push(kind(TimeRange::new(
+            config.wake_time + from,
+            config.wake_time + to,
+        )));
+    }
+}
+
+/// Turns travel that isn't between tasks at different locations into free time, merging it
+/// with the free time around it.
+fn settle(schedule: Vec<Schedule>) -> Vec<Schedule> {
+    let locations = schedule
+        .iter()
+        .map(|entry| entry.get_task().map(|task| task.location.as_deref()))
+        .collect::<Vec<_>>();
+
+    let mut settled: Vec<Schedule> = Vec::with_capacity(schedule.len());
+    for (i, entry) in schedule.into_iter().enumerate() {
+        let entry = match entry {
+            Schedule::Travel(range) => {
+                let from = locations[..i].iter().rev().find_map(|location| *location);
+                let to = locations[i + 1..].iter().find_map(|location| *location);
+                match (from, to) {
+                    (Some(Some(from)), Some(Some(to))) if from != to => Schedule::Travel(range),
+                    _ => Schedule::Free(range),
+                }
             }
-            free.push(Schedule::Static(times[i].1));
+            entry => entry,
+        };
 
-            if i == times_len - 1 && times[i].0.end != config.bed_time {
-                free.push(Schedule::Free(TimeRange::new(
-                    times[i].0.end,
-                    config.bed_time,
-                )))
+        if let (Some(Schedule::Free(last)), Schedule::Free(range)) = (settled.last_mut(), &entry) {
+            if last.end == range.start {
+                *last = TimeRange::new(last.start, range.end);
+                continue;
             }
         }
-
-        free
+        settled.push(entry);
     }
+    settled
+}
+
+/// Time kept free around a task, see [`Config::padding`].
+#[derive(Debug, Clone, Copy)]
+struct Padding {
+    /// Getting to the task, before the buffer before it.
+    travel: Duration,
+    before: Duration,
+    after: Duration,
 }
 
 #[derive(Debug)]
 pub enum Schedule<'a> {
     Static(&'a StaticTask),
-    DynamicTask(&'a DynamicTask),
-    DynamicPart(DynamicTask),
+    DynamicTask(&'a DynamicTask, TimeRange),
+    /// The `nth` part, counting from 1 in the order they happen, of the splittable task `of`.
+    DynamicPart {
+        part: Box<DynamicTask>,
+        of: &'a DynamicTask,
+        nth: usize,
+        time: TimeRange,
+    },
+    /// Time kept free before or after a task.
+    Buffer(TimeRange),
+    /// Getting to a task at a different location than the task before it.
+    Travel(TimeRange),
+    Break(&'a Break, TimeRange),
     Free(TimeRange),
 }
 
+impl<'a> Schedule<'a> {
+    pub fn get_time(&self) -> TimeRange {
+        match self {
+            Schedule::Static(task) => task.time,
+            Schedule::DynamicTask(_, time)
+            | Schedule::DynamicPart { time, .. }
+            | Schedule::Buffer(time)
+            | Schedule::Travel(time)
+            | Schedule::Break(_, time)
+            | Schedule::Free(time) => *time,
+        }
+    }
+
+    /// The task of the entry, the whole task for a part.
+    pub fn get_task(&self) -> Option<&'a Task> {
+        match self {
+            Schedule::Static(task) => Some(&task.task),
+            Schedule::DynamicTask(task, _) | Schedule::DynamicPart { of: task, .. } => {
+                Some(task.get_task())
+            }
+            Schedule::Buffer(_) | Schedule::Travel(_) | Schedule::Break(..) | Schedule::Free(_) => {
+                None
+            }
+        }
+    }
+}
+
+#[serde_as]
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Task {
     uuid: Uuid,
     name: String,
     description: String,
+    /// Time kept free before and after the task, the config's buffer if `None`.
+    #[serde_as(as = "Option<(DurationSeconds<i64>, DurationSeconds<i64>)>")]
+    #[serde(default)]
+    padding: Option<(Duration, Duration)>,
+    #[serde(default)]
+    location: Option<String>,
 }
 
 impl Task {
@@ -408,8 +1567,43 @@ impl Task {
             uuid: Uuid::new_v4(),
             name,
             description,
+            padding: None,
+            location: None,
         }
     }
+
+    /// Keeps `before` and `after` free around the task instead of the config's buffer.
+    pub fn padding(mut self, before: Duration, after: Duration) -> Self {
+        self.padding = Some((before, after));
+        self
+    }
+
+    /// Where the task happens, getting there from a task somewhere else takes the config's
+    /// travel time.
+    pub fn location(mut self, location: String) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    pub fn get_uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn get_padding(&self) -> Option<&(Duration, Duration)> {
+        self.padding.as_ref()
+    }
+
+    pub fn get_location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -434,6 +1628,14 @@ impl StaticTask {
     pub fn new(task: Task, time: TimeRange) -> Self {
         Self { task, time }
     }
+
+    pub fn get_task(&self) -> &Task {
+        &self.task
+    }
+
+    pub fn get_time(&self) -> &TimeRange {
+        &self.time
+    }
 }
 
 #[serde_as]
@@ -447,6 +1649,21 @@ pub enum DynamicTask {
         around: PartOfDay,
         can_split: bool,
         priority: i32,
+        /// Last date the task can be done on, with `date` being the first. The planner picks
+        /// the date it's scheduled on.
+        #[serde(default)]
+        deadline: Option<NaiveDate>,
+        /// Shortest part the task can be split into.
+        #[serde_as(as = "Option<DurationSeconds<i64>>")]
+        #[serde(default)]
+        min_chunk: Option<Duration>,
+        /// Longest part the task can be split into.
+        #[serde_as(as = "Option<DurationSeconds<i64>>")]
+        #[serde(default)]
+        max_chunk: Option<Duration>,
+        /// Most parts the task can be split into.
+        #[serde(default)]
+        max_parts: Option<usize>,
     },
     Fixed {
         task: StaticTask,
@@ -470,6 +1687,10 @@ impl DynamicTask {
             around,
             can_split,
             priority: 0,
+            deadline: None,
+            min_chunk: None,
+            max_chunk: None,
+            max_parts: None,
         }
     }
 
@@ -489,32 +1710,178 @@ impl DynamicTask {
         self
     }
 
-    fn get_date(&self) -> &NaiveDate {
+    /// Lets the planner schedule the task on any day from its date to `deadline`.
+    pub fn deadline(mut self, deadline: NaiveDate) -> Result<Self, LuniteError> {
+        match &mut self {
+            DynamicTask::Flexible {
+                date,
+                deadline: old,
+                ..
+            } => {
+                if deadline < *date {
+                    return Err(LuniteError::DeadlineBeforeStart {
+                        start: *date,
+                        deadline,
+                    });
+                }
+                *old = Some(deadline);
+            }
+            DynamicTask::Fixed { .. } => return Err(LuniteError::ExpectedFlexible),
+        }
+        Ok(self)
+    }
+
+    /// Limits the parts a splittable task is split into to be `min` to `max` long, with at most
+    /// `max_parts` of them. Fails if no number of parts within the limits makes up the task, or
+    /// if the task can't be split and there are any limits.
+    pub fn chunks(
+        mut self,
+        min: Option<Duration>,
+        max: Option<Duration>,
+        max_parts: Option<usize>,
+    ) -> Result<Self, LuniteError> {
+        match &mut self {
+            DynamicTask::Flexible {
+                length,
+                can_split,
+                min_chunk,
+                max_chunk,
+                max_parts: old,
+                ..
+            } => {
+                if !*can_split && (min.is_some() || max.is_some() || max_parts.is_some()) {
+                    return Err(LuniteError::ExpectedSplittable);
+                }
+                let positive = min.is_none_or(|min| min > Duration::zero())
+                    && max.is_none_or(|max| max > Duration::zero())
+                    && max_parts.is_none_or(|parts| parts > 0);
+                // some number of parts between the fewest the longest parts make up the task
+                // with and the most the shortest ones do
+                let fits = positive && {
+                    let length = length.num_milliseconds();
+                    let fewest = max.map_or(1, |max| {
+                        let max = max.num_milliseconds();
+                        (length + max - 1) / max
+                    });
+                    let most = min
+                        .map_or(i64::MAX, |min| length / min.num_milliseconds())
+                        .min(max_parts.map_or(i64::MAX, |parts| parts as i64));
+                    fewest <= most
+                };
+                if !fits {
+                    return Err(LuniteError::InvalidChunks {
+                        length: *length,
+                        min,
+                        max,
+                        max_parts,
+                    });
+                }
+
+                *min_chunk = min;
+                *max_chunk = max;
+                *old = max_parts;
+            }
+            DynamicTask::Fixed { .. } => return Err(LuniteError::ExpectedFlexible),
+        }
+        Ok(self)
+    }
+
+    pub fn get_task(&self) -> &Task {
+        match self {
+            DynamicTask::Flexible { task, .. }
+            | DynamicTask::Fixed {
+                task: StaticTask { task, .. },
+                ..
+            } => task,
+        }
+    }
+
+    pub fn get_priority(&self) -> i32 {
+        match self {
+            DynamicTask::Flexible { priority, .. } | DynamicTask::Fixed { priority, .. } => {
+                *priority
+            }
+        }
+    }
+
+    pub fn get_date(&self) -> &NaiveDate {
         match self {
             DynamicTask::Flexible { date, .. } | DynamicTask::Fixed { date, .. } => date,
         }
     }
 
-    fn fixed_split(&self, length: &Duration, nth: usize) -> Result<Self, String> {
+    pub fn get_deadline(&self) -> Option<&NaiveDate> {
+        match self {
+            DynamicTask::Flexible { deadline, .. } => deadline.as_ref(),
+            DynamicTask::Fixed { .. } => None,
+        }
+    }
+
+    /// The shortest and longest part the task can be split into and how many parts at most.
+    pub fn get_chunks(&self) -> (Option<&Duration>, Option<&Duration>, Option<usize>) {
+        match self {
+            DynamicTask::Flexible {
+                min_chunk,
+                max_chunk,
+                max_parts,
+                ..
+            } => (min_chunk.as_ref(), max_chunk.as_ref(), *max_parts),
+            DynamicTask::Fixed { .. } => (None, None, None),
+        }
+    }
+
+    /// The `nth` part of the task, `length` long. Fails if the part is outside of the task's
+    /// chunk limits.
+    pub fn fixed_split(&self, length: &Duration, nth: usize) -> Result<Self, LuniteError> {
         match self {
             Self::Flexible {
-                task: Task {
-                    name, description, ..
-                },
+                task:
+                    Task {
+                        name,
+                        description,
+                        padding,
+                        location,
+                        ..
+                    },
                 date,
                 around,
                 can_split,
                 priority,
+                deadline,
+                min_chunk,
+                max_chunk,
+                max_parts,
                 ..
-            } => Ok(Self::Flexible {
-                task: Task::new(format!("{name}-{nth}"), description.to_owned()),
-                date: date.to_owned(),
-                length: length.to_owned(),
-                around: *around,
-                can_split: *can_split,
-                priority: *priority,
-            }),
-            _ => Err(String::from("Expected a flexible dynamic task")),
+            } => {
+                if nth == 0
+                    || min_chunk.is_some_and(|min| *length < min)
+                    || max_chunk.is_some_and(|max| *length > max)
+                    || max_parts.is_some_and(|max| nth > max)
+                {
+                    return Err(LuniteError::InvalidChunk {
+                        length: *length,
+                        nth,
+                    });
+                }
+
+                Ok(Self::Flexible {
+                    task: Task {
+                        padding: *padding,
+                        location: location.clone(),
+                        ..Task::new(format!("{name}-{nth}"), description.to_owned())
+                    },
+                    date: date.to_owned(),
+                    length: length.to_owned(),
+                    around: *around,
+                    can_split: *can_split,
+                    priority: *priority,
+                    deadline: *deadline,
+                    min_chunk: *min_chunk,
+                    max_chunk: *max_chunk,
+                    max_parts: *max_parts,
+                })
+            }
+            _ => Err(LuniteError::ExpectedFlexible),
         }
     }
 }
@@ -573,22 +1940,76 @@ pub struct TimeRange {
 }
 
 impl TimeRange {
+    /// A range ending at or before its start runs past midnight into the next day, so
+    /// `22:00-02:00` lasts four hours and `06:00-06:00` a whole day.
     pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
         Self { start, end }
     }
 
     pub fn overlap(&self, other: &Self) -> bool {
-        (other.start >= self.start && other.start < self.end)
-            || (other.end > self.start && other.end <= self.end)
+        let (start, end) = self.span();
+        other
+            .spans()
+            .any(|(other_start, other_end)| start < other_end && other_start < end)
     }
 
     pub fn subset(&self, other: &Self) -> bool {
-        (self.start >= other.start && other.start < self.end)
-            && (self.end > other.start && other.end >= self.end)
+        let (start, end) = self.span();
+        other
+            .spans()
+            .any(|(other_start, other_end)| start >= other_start && other_end >= end)
+    }
+
+    pub fn get_start(&self) -> NaiveTime {
+        self.start
+    }
+
+    pub fn get_end(&self) -> NaiveTime {
+        self.end
+    }
+
+    /// Whether the range ends on the day after it starts.
+    pub fn ends_next_day(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// The earliest part of `self` that's also in `other`.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (start, end) = self.span();
+        other
+            .spans()
+            .filter_map(|(other_start, other_end)| {
+                let start = start.max(other_start);
+                let end = end.min(other_end);
+                (start < end).then_some((start, end))
+            })
+            .min()
+            .map(|(start, end)| Self::new(midnight() + start, midnight() + end))
     }
 
     pub fn to_duration(&self) -> Duration {
-        self.end.signed_duration_since(self.start)
+        let duration = self.end.signed_duration_since(self.start);
+        if duration <= Duration::zero() {
+            duration + Duration::days(1)
+        } else {
+            duration
+        }
+    }
+
+    /// Start and end as durations since the midnight the range starts after.
+    fn span(&self) -> (Duration, Duration) {
+        let start = self.start.signed_duration_since(midnight());
+        (start, start + self.to_duration())
+    }
+
+    /// [`TimeRange::span`] of the range on the day before, the same and the day after, so
+    /// ranges around midnight can be compared.
+    fn spans(&self) -> impl Iterator<Item = (Duration, Duration)> {
+        let (start, end) = self.span();
+        [-1, 0, 1].into_iter().map(move |days| {
+            let shift = Duration::days(days);
+            (start + shift, end + shift)
+        })
     }
 }
 
@@ -604,6 +2025,66 @@ impl PartialOrd for TimeRange {
     }
 }
 
-fn current() -> usize {
-    Local::now().weekday().num_days_from_monday() as usize
+fn system_clock() -> Box<dyn Clock> {
+    Box::new(SystemClock)
+}
+
+fn system_time_zone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+fn midnight() -> NaiveTime {
+    NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+}
+
+/// Dates [`Planner::deadline_plan`] picked for tasks with a deadline, by their uuid. Tasks
+/// with a deadline added after it was made aren't on any date.
+#[derive(Debug, Default)]
+pub struct DeadlinePlan {
+    dates: HashMap<Uuid, NaiveDate>,
+    at_risk: Vec<Uuid>,
+}
+
+impl DeadlinePlan {
+    /// Date `task` is scheduled on, if any.
+    fn date_of(&self, task: &DynamicTask) -> Option<NaiveDate> {
+        match task.get_deadline() {
+            Some(_) => self.dates.get(&task.get_task().uuid).copied(),
+            None => Some(*task.get_date()),
+        }
+    }
+}
+
+/// Uuids of the tasks missing from the schedules of the dates tried with a set of tasks, by
+/// the date and the sorted uuids of the tasks, so no day is scheduled twice with the same
+/// tasks.
+type Tried = HashMap<(NaiveDate, Vec<Uuid>), Vec<Uuid>>;
+
+/// Uuids of the tasks `diagnostics` say are missing from the schedule.
+fn unplaced(diagnostics: &[ScheduleDiagnostic]) -> impl Iterator<Item = &Uuid> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_unplaced())
+        .filter_map(ScheduleDiagnostic::task_uuid)
+}
+
+/// Fixed tasks can't move so they go first, flexible ones then claim free time by priority.
+fn placement_order(task: &&DynamicTask) -> (bool, Reverse<i32>) {
+    (
+        matches!(task, DynamicTask::Flexible { .. }),
+        Reverse(task.get_priority()),
+    )
+}
+
+/// Index into [`Planner::days`] of the weekday of `date`.
+fn weekday_index(date: NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+/// The weekday of the `nth` day of the week, counting from Monday as 0.
+fn nth_weekday(nth: usize) -> Weekday {
+    Weekday::try_from(nth as u8).expect("days of the week are checked to be below 7")
 }
@@ -1,82 +1,665 @@
-#![allow(deprecated)]
-
-use chrono::{Duration, NaiveDate, NaiveTime};
-use lunite::{Config, DynamicTask, PartOfDay, Planner, StaticTask, Task, TimeRange};
-
-fn main() {
-    let mut planner = Planner::new(Config::new(
-        NaiveTime::from_hms(5, 30, 0),
-        NaiveTime::from_hms(21, 0, 0),
-    ));
-    let task1 = StaticTask::new(
-        Task::new(format!("task1"), format!("")),
-        TimeRange::new(NaiveTime::from_hms(6, 0, 0), NaiveTime::from_hms(8, 0, 0)),
-    );
-    let task2 = StaticTask::new(
-        Task::new(format!("task2"), format!("")),
-        TimeRange::new(NaiveTime::from_hms(10, 0, 0), NaiveTime::from_hms(12, 0, 0)),
-    );
-    let task3 = StaticTask::new(
-        Task::new(format!("task3"), format!("")),
-        TimeRange::new(NaiveTime::from_hms(14, 0, 0), NaiveTime::from_hms(16, 0, 0)),
-    );
-
-    planner.current_day_mut().add_static(task1);
-    planner.current_day_mut().add_static(task2);
-    planner.current_day_mut().add_static(task3);
-
-    let dynamic_tasks = vec![
-        DynamicTask::new_fixed(
-            StaticTask::new(
-                Task::new("task4".to_string(), "".to_string()),
-                TimeRange::new(
-                    NaiveTime::from_hms_opt(5, 40, 0).unwrap(),
-                    NaiveTime::from_hms_opt(5, 50, 0).unwrap(),
-                ),
-            ),
-            NaiveDate::from_ymd_opt(2023, 4, 26).unwrap(),
-        ),
-        DynamicTask::new_fixed(
-            StaticTask::new(
-                Task::new("task5".to_string(), "".to_string()),
-                TimeRange::new(
-                    NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
-                    NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
-                ),
-            ),
-            NaiveDate::from_ymd_opt(2023, 4, 26).unwrap(),
-        ),
-        DynamicTask::new_flexible(
-            Task::new("task6".to_string(), "".to_string()),
-            NaiveDate::from_ymd_opt(2023, 4, 26).unwrap(),
-            Duration::minutes(120),
-            PartOfDay::Morning,
-            true,
-        ),
-        DynamicTask::new_fixed(
-            StaticTask::new(
-                Task::new("task4".to_string(), "".to_string()),
-                TimeRange::new(
-                    NaiveTime::from_hms_opt(12, 30, 0).unwrap(),
-                    NaiveTime::from_hms_opt(12, 45, 0).unwrap(),
-                ),
-            ),
-            NaiveDate::from_ymd_opt(2023, 4, 27).unwrap(),
-        ),
-        DynamicTask::new_flexible(
-            Task::new("task5".to_string(), "".to_string()),
-            NaiveDate::from_ymd_opt(2023, 4, 26).unwrap(),
-            Duration::minutes(180),
-            PartOfDay::Night,
-            false,
-        ),
-    ];
-
-    for task in dynamic_tasks {
-        planner.add_dynamic(task).unwrap();
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand};
+use lunite::{
+    parse::{parse_date, parse_duration, parse_range, parse_time, parse_weekday},
+    render::{DayView, WeekView},
+    Break, BreakRule, Config, DeadlinePlan, DynamicTask, PartOfDay, Planner, Recurrence,
+    RecurringTask, Rollover, Rule, Schedule, ScheduleDiagnostic, StaticTask, Strategy, Task,
+    TimeRange,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Plan your days around fixed and flexible tasks")]
+struct Cli {
+    /// Planner file to use, defaults to $LUNITE_FILE or $XDG_DATA_HOME/lunite/planner.json
+    #[arg(short, long, global = true)]
+    file: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add a task repeating every week on the same day and time
+    AddStatic {
+        /// Weekday the task happens on, e.g. `mon`
+        #[arg(value_parser = parse_weekday)]
+        weekday: Weekday,
+        /// When the task happens, e.g. `14:00-16:00`
+        #[arg(value_parser = parse_range)]
+        time: TimeRange,
+        name: String,
+        #[arg(short, long, default_value = "")]
+        description: String,
+        /// Add the task even if it overlaps another one
+        #[arg(long)]
+        allow_overlap: bool,
+        #[command(flatten)]
+        surroundings: Surroundings,
+    },
+    /// Add a task repeating on a rule, e.g. `biweekly`, `every-3-days`, `weekdays` or `last-fri`
+    AddRecurring {
+        /// daily, weekdays, weekly, biweekly, every-N-days, every-N-weeks or e.g. 2nd-tue
+        rule: Rule,
+        /// When the task happens, e.g. `14:00-16:00`
+        #[arg(value_parser = parse_range)]
+        time: TimeRange,
+        name: String,
+        /// First day of the task, weekly rules repeat on its weekday
+        #[arg(long, default_value = "today")]
+        from: String,
+        /// Last day the task can happen on
+        #[arg(long)]
+        until: Option<String>,
+        /// Days to leave out, can be given multiple times
+        #[arg(long)]
+        except: Vec<String>,
+        #[arg(short, long, default_value = "")]
+        description: String,
+        /// Add the task even if it overlaps another one
+        #[arg(long)]
+        allow_overlap: bool,
+        #[command(flatten)]
+        surroundings: Surroundings,
+    },
+    /// Add a one-off task, either at a fixed time (`--at`) or of a given length (`--length`)
+    AddDynamic {
+        name: String,
+        /// Day of the task, e.g. `tomorrow`, `fri`, `+3` or `2023-04-26`
+        #[arg(long, default_value = "today")]
+        date: String,
+        /// Fixed time of the task, e.g. `14:00-16:00`
+        #[arg(long, value_parser = parse_range, conflicts_with_all = ["length", "around", "split"])]
+        at: Option<TimeRange>,
+        /// Length of a flexible task, e.g. `90m` or `1h30m`
+        #[arg(long, value_parser = parse_duration, required_unless_present = "at")]
+        length: Option<Duration>,
+        /// Preferred part of the day: morning, afternoon, evening, night or a range
+        #[arg(long, default_value = "morning")]
+        around: PartOfDay,
+        /// Allow splitting the task into multiple parts
+        #[arg(long)]
+        split: bool,
+        /// Shortest part a split task can have, e.g. `30m`
+        #[arg(long, value_parser = parse_duration, requires = "split")]
+        min_chunk: Option<Duration>,
+        /// Longest part a split task can have, e.g. `2h`
+        #[arg(long, value_parser = parse_duration, requires = "split")]
+        max_chunk: Option<Duration>,
+        /// Most parts a split task can have
+        #[arg(long, requires = "split")]
+        max_parts: Option<usize>,
+        /// Last day the task can be done on, lets the planner pick a day from `--date` on
+        #[arg(long, conflicts_with = "at")]
+        deadline: Option<String>,
+        #[arg(short, long, default_value_t = 0, allow_negative_numbers = true)]
+        priority: i32,
+        #[arg(short, long, default_value = "")]
+        description: String,
+        #[command(flatten)]
+        surroundings: Surroundings,
+    },
+    /// Mark a task of today as done
+    Complete { name: String },
+    /// Show the tasks that weren't done in time
+    Overdue,
+    /// Show the tasks done between two days, the current week by default
+    History {
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Show the schedule of a day, today by default
+    Schedule { day: Option<String> },
+    /// Show the free time of a day, today by default
+    Free { day: Option<String> },
+    /// Show the schedules of a week side by side, the current one by default
+    Week {
+        /// Any day of the week to show
+        day: Option<String>,
+    },
+    /// Skip a static or recurring task on a single day
+    Skip { day: String, name: String },
+    /// Export schedules of the given days as an iCalendar, the current week by default
+    Export {
+        days: Vec<String>,
+        /// File to write the calendar to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import tasks from an iCalendar file
+    Import {
+        calendar: PathBuf,
+        /// Part of the day for to-dos: morning, afternoon, evening, night or a range
+        #[arg(long, default_value = "morning")]
+        around: PartOfDay,
+    },
+    /// Set the wake and bed time of a weekday, `weekdays`, `weekends` or a single date
+    Hours {
+        /// e.g. `sat`, `weekends` or `2023-12-24`, weekdays stand for every week
+        days: String,
+        #[arg(value_parser = parse_time, required_unless_present = "reset")]
+        wake: Option<NaiveTime>,
+        #[arg(value_parser = parse_time, required_unless_present = "reset")]
+        bed: Option<NaiveTime>,
+        /// Go back to the default wake and bed time
+        #[arg(long, conflicts_with_all = ["wake", "bed"])]
+        reset: bool,
+    },
+    /// Keep a break free of flexible tasks every day, at a time or after every stretch of work
+    Protect {
+        name: String,
+        /// When the break is, e.g. `12:00-13:00`
+        #[arg(value_parser = parse_range, required_unless_present = "every")]
+        time: Option<TimeRange>,
+        /// How far the break can move to make way for static tasks, e.g. `30m`
+        #[arg(long, value_parser = parse_duration, conflicts_with = "every")]
+        flexibility: Option<Duration>,
+        /// Take the break after every stretch of tasks this long, e.g. `90m`
+        #[arg(long, value_parser = parse_duration, requires = "length", conflicts_with = "time")]
+        every: Option<Duration>,
+        /// How long a break taken `--every` stretch is, e.g. `10m`
+        #[arg(long, value_parser = parse_duration, requires = "every")]
+        length: Option<Duration>,
+    },
+    /// Stop keeping a break
+    Unprotect { name: String },
+    /// Show the config, or change it with the given options
+    Config {
+        #[arg(long, value_parser = parse_time)]
+        wake: Option<NaiveTime>,
+        #[arg(long, value_parser = parse_time)]
+        bed: Option<NaiveTime>,
+        /// first-fit, best-fit, earliest-deadline-first, spread-evenly or optimal
+        #[arg(long)]
+        strategy: Option<Strategy>,
+        /// IANA time zone like `Europe/Prague`, the system's by default
+        #[arg(long)]
+        time_zone: Option<Tz>,
+        /// What happens to unfinished tasks: keep (the default), drop, next-free-day or overdue
+        #[arg(long)]
+        rollover: Option<Rollover>,
+        /// Time kept free before and after every task, e.g. `10m`
+        #[arg(long, value_parser = parse_duration)]
+        buffer: Option<Duration>,
+        /// Time it takes to get between tasks at different locations, e.g. `30m`
+        #[arg(long, value_parser = parse_duration)]
+        travel: Option<Duration>,
+        /// Least time kept between the parts of a split task, e.g. `15m`
+        #[arg(long, value_parser = parse_duration)]
+        part_gap: Option<Duration>,
+    },
+}
+
+/// What a task needs around it.
+#[derive(Args)]
+struct Surroundings {
+    /// Time kept free before the task, e.g. `10m`, the configured buffer by default
+    #[arg(long, value_parser = parse_duration)]
+    before: Option<Duration>,
+    /// Time kept free after the task, the configured buffer by default
+    #[arg(long, value_parser = parse_duration)]
+    after: Option<Duration>,
+    /// Where the task happens, getting there from a task elsewhere takes the travel time
+    #[arg(long)]
+    location: Option<String>,
+}
+
+impl Surroundings {
+    fn task(self, name: String, description: String, config: &Config) -> Task {
+        let mut task = Task::new(name, description);
+        if self.before.is_some() || self.after.is_some() {
+            task = task.padding(
+                self.before.unwrap_or(config.get_buffer()),
+                self.after.unwrap_or(config.get_buffer()),
+            );
+        }
+        if let Some(location) = self.location {
+            task = task.location(location);
+        }
+        task
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let path = match cli.file {
+        Some(path) => path,
+        None => default_path()?,
+    };
+    let mut planner = if path.exists() {
+        Planner::load(&path)?
+    } else {
+        Planner::new(Config::new(
+            NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+        ))
+    };
+    if planner.advance() {
+        save(&planner, &path)?;
+    }
+    let today = planner.today();
+
+    match cli.command {
+        Command::AddStatic {
+            weekday,
+            time,
+            name,
+            description,
+            allow_overlap,
+            surroundings,
+        } => {
+            let n = weekday.num_days_from_monday() as usize;
+            let task = surroundings.task(name, description, planner.get_config());
+            let task = StaticTask::new(task, time);
+            if allow_overlap {
+                planner.add_static_allow_overlap(n, task)?;
+            } else {
+                planner.add_static(n, task)?;
+            }
+        }
+        Command::AddRecurring {
+            rule,
+            time,
+            name,
+            from,
+            until,
+            except,
+            description,
+            allow_overlap,
+            surroundings,
+        } => {
+            let mut recurrence = Recurrence::new(rule, parse_date(&from, today)?);
+            if let Some(until) = until {
+                recurrence = recurrence.until(parse_date(&until, today)?);
+            }
+            for date in except {
+                recurrence = recurrence.except(parse_date(&date, today)?);
+            }
+            let task = surroundings.task(name, description, planner.get_config());
+            let task = StaticTask::new(task, time);
+            let task = RecurringTask::new(task, recurrence);
+            if allow_overlap {
+                planner.add_recurring_allow_overlap(task)?;
+            } else {
+                planner.add_recurring(task)?;
+            }
+        }
+        Command::AddDynamic {
+            name,
+            date,
+            at,
+            length,
+            around,
+            split,
+            min_chunk,
+            max_chunk,
+            max_parts,
+            deadline,
+            priority,
+            description,
+            surroundings,
+        } => {
+            let date = parse_date(&date, today)?;
+            let task = surroundings.task(name, description, planner.get_config());
+            let mut task = match (at, length) {
+                (Some(time), _) => DynamicTask::new_fixed(StaticTask::new(task, time), date),
+                (None, Some(length)) => {
+                    DynamicTask::new_flexible(task, date, length, around, split)
+                        .chunks(min_chunk, max_chunk, max_parts)?
+                }
+                (None, None) => unreachable!("clap requires --length without --at"),
+            };
+            if let Some(deadline) = deadline {
+                task = task.deadline(parse_date(&deadline, today)?)?;
+            }
+            planner.add_dynamic(task.priority(priority))?;
+        }
+        Command::Complete { name } => complete(&mut planner, &name)?,
+        Command::Overdue => {
+            for (task, due) in planner.overdue() {
+                println!("{due}  {}", task.get_task().get_name());
+            }
+            return Ok(());
+        }
+        Command::History { from, to } => {
+            let from = match from {
+                Some(from) => parse_date(&from, today)?,
+                None => monday_of(today),
+            };
+            let to = day_date(to.as_deref(), today)?;
+            for completion in planner.completions_between(from, to) {
+                println!(
+                    "{}  {}  {}",
+                    completion.date,
+                    completion.done_at.format("%Y-%m-%d %H:%M"),
+                    completion.task.get_name()
+                );
+            }
+            return Ok(());
+        }
+        Command::Schedule { day } => {
+            let date = day_date(day.as_deref(), today)?;
+            let plan = planner.deadline_plan();
+            let (schedule, diagnostics) = planner.schedule_planned(date, &plan);
+            print!("{}", DayView::new(&schedule));
+            for diagnostic in &diagnostics {
+                warn(&planner, diagnostic);
+            }
+            warn_at_risk(&planner, &plan);
+            return Ok(());
+        }
+        Command::Free { day } => {
+            let date = day_date(day.as_deref(), today)?;
+            let (schedule, _) = planner.schedule_for(date);
+            let free = schedule
+                .into_iter()
+                .filter(|schedule| matches!(schedule, Schedule::Free(_)))
+                .collect::<Vec<_>>();
+            print!("{}", DayView::new(&free));
+            return Ok(());
+        }
+        Command::Week { day } => {
+            let monday = monday_of(day_date(day.as_deref(), today)?);
+            let plan = planner.deadline_plan();
+            let mut days = vec![];
+            let mut diagnostics = vec![];
+            for date in monday.iter_days().take(7) {
+                let (schedule, mut day_diagnostics) = planner.schedule_planned(date, &plan);
+                days.push(schedule);
+                diagnostics.append(&mut day_diagnostics);
+            }
+            print!("{}", WeekView::new(&days));
+            for diagnostic in &diagnostics {
+                warn(&planner, diagnostic);
+            }
+            warn_at_risk(&planner, &plan);
+            return Ok(());
+        }
+        Command::Skip { day, name } => {
+            let date = day_date(Some(&day), today)?;
+            let weekday = date.weekday().num_days_from_monday() as usize;
+            let is_named = |task: &StaticTask| task.get_task().get_name() == name;
+            if let Some(i) = planner
+                .nth_day(weekday)?
+                .get_static_tasks()
+                .iter()
+                .position(is_named)
+            {
+                planner.skip_static(date, i)?;
+            } else {
+                let i = planner
+                    .get_recurring()
+                    .iter()
+                    .position(|task| {
+                        task.get_recurrence().occurs_on(date) && is_named(task.get_task())
+                    })
+                    .ok_or_else(|| format!("There is no task called {name:?} on {date}"))?;
+                planner.skip_recurring(date, i)?;
+            }
+        }
+        Command::Export { days, output } => {
+            let dates = if days.is_empty() {
+                let monday = monday_of(today);
+                (0..7).map(|n| monday + Duration::days(n)).collect()
+            } else {
+                days.iter()
+                    .map(|day| day_date(Some(day), today))
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+            let ics = planner.to_ics(dates)?;
+            match output {
+                Some(output) => fs::write(&output, ics)
+                    .map_err(|e| format!("Couldn't write {}: {e}", output.display()))?,
+                None => print!("{ics}"),
+            }
+            return Ok(());
+        }
+        Command::Hours {
+            days,
+            wake,
+            bed,
+            reset,
+        } => {
+            let config = planner.get_config().clone();
+            let weekdays = match days.as_str() {
+                "weekdays" => vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                ],
+                "weekends" => vec![Weekday::Sat, Weekday::Sun],
+                days => parse_weekday(days)
+                    .map(|weekday| vec![weekday])
+                    .unwrap_or_default(),
+            };
+            let hours = if reset { None } else { wake.zip(bed) };
+            let config = match (weekdays.is_empty(), hours) {
+                (false, None) => weekdays
+                    .into_iter()
+                    .fold(config, |config, weekday| config.reset_weekday(weekday)),
+                (false, Some((wake, bed))) => weekdays
+                    .into_iter()
+                    .fold(config, |config, weekday| config.weekday(weekday, wake, bed)),
+                (true, None) => config.reset_date(parse_date(&days, today)?),
+                (true, Some((wake, bed))) => config.date(parse_date(&days, today)?, wake, bed),
+            };
+            planner.set_config(config);
+        }
+        Command::Protect {
+            name,
+            time,
+            flexibility,
+            every,
+            length,
+        } => {
+            let brk = match (time, every, length) {
+                (Some(time), _, _) => {
+                    Break::at(name, time, flexibility.unwrap_or_else(Duration::zero))
+                }
+                (None, Some(work), Some(length)) => Break::every(name, work, length),
+                _ => unreachable!("clap requires a time or --every with --length"),
+            };
+            planner.set_config(planner.get_config().clone().protect(brk));
+        }
+        Command::Unprotect { name } => {
+            if !planner
+                .get_config()
+                .get_breaks()
+                .iter()
+                .any(|brk| brk.get_name() == name)
+            {
+                return Err(format!("There is no break called {name}").into());
+            }
+            planner.set_config(planner.get_config().clone().unprotect(&name));
+        }
+        Command::Import { calendar, around } => {
+            let ics = fs::read_to_string(&calendar)
+                .map_err(|e| format!("Couldn't read {}: {e}", calendar.display()))?;
+            let report = planner.import_ics(&ics, around)?;
+            println!(
+                "imported {} static and {} dynamic tasks",
+                report.statics, report.dynamics
+            );
+            for (summary, e) in report.skipped {
+                eprintln!("skipped {summary}: {e}");
+            }
+        }
+        Command::Config {
+            wake,
+            bed,
+            strategy,
+            time_zone,
+            rollover,
+            buffer,
+            travel,
+            part_gap,
+        } => {
+            let config = planner.get_config();
+            if wake.is_none()
+                && bed.is_none()
+                && strategy.is_none()
+                && time_zone.is_none()
+                && rollover.is_none()
+                && buffer.is_none()
+                && travel.is_none()
+                && part_gap.is_none()
+            {
+                println!("wake time: {}", config.get_wake_time().format("%H:%M"));
+                println!("bed time:  {}", config.get_bed_time().format("%H:%M"));
+                let mut weekday = Weekday::Mon;
+                for _ in 0..7 {
+                    let day = config.get_day_on_weekday(weekday);
+                    if day != config.get_day() {
+                        println!("{:<10} {day}", format!("{weekday}:"));
+                    }
+                    weekday = weekday.succ();
+                }
+                for (date, day) in config.get_dates() {
+                    println!("{date}: {day}");
+                }
+                println!("strategy:  {:?}", config.get_strategy());
+                println!("time zone: {}", config.get_time_zone());
+                println!("rollover:  {:?}", config.get_rollover());
+                println!("buffer:    {}m", config.get_buffer().num_minutes());
+                println!("travel:    {}m", config.get_travel().num_minutes());
+                println!("part gap:  {}m", config.get_part_gap().num_minutes());
+                for brk in config.get_breaks() {
+                    match brk.get_rule() {
+                        BreakRule::At { time, flexibility } => println!(
+                            "break:     {} at {time}, ±{}m",
+                            brk.get_name(),
+                            flexibility.num_minutes()
+                        ),
+                        BreakRule::Every { work, length } => println!(
+                            "break:     {} of {}m every {}m",
+                            brk.get_name(),
+                            length.num_minutes(),
+                            work.num_minutes()
+                        ),
+                    }
+                }
+                return Ok(());
+            }
+
+            let config = config
+                .clone()
+                .day(
+                    wake.unwrap_or(config.get_wake_time()),
+                    bed.unwrap_or(config.get_bed_time()),
+                )
+                .strategy(strategy.unwrap_or(config.get_strategy()))
+                .time_zone(time_zone.unwrap_or(config.get_time_zone()))
+                .rollover(rollover.unwrap_or(config.get_rollover()))
+                .buffer(buffer.unwrap_or(config.get_buffer()))
+                .travel(travel.unwrap_or(config.get_travel()))
+                .part_gap(part_gap.unwrap_or(config.get_part_gap()));
+            planner.set_config(config);
+        }
     }
 
-    // planner.complete_dynamic(2).unwrap();
-    planner.current_day_mut().complete_static(1).unwrap();
-    println!("{:#?}", planner.get_schedule_with_dynamics());
+    save(&planner, &path)
+}
+
+fn save(planner: &Planner, path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Couldn't create {}: {e}", parent.display()))?;
+    }
+    Ok(planner.save(path)?)
+}
+
+fn default_path() -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(path) = env::var_os("LUNITE_FILE") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let data = match env::var_os("XDG_DATA_HOME") {
+        Some(data) => PathBuf::from(data),
+        None => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".local/share"))
+            .ok_or("Couldn't find a place for the planner file, pass one with --file")?,
+    };
+    Ok(data.join("lunite").join("planner.json"))
+}
+
+/// Date `day` refers to, today if not given.
+fn day_date(day: Option<&str>, today: NaiveDate) -> Result<NaiveDate, Box<dyn Error>> {
+    Ok(match day {
+        Some(day) => parse_date(day, today)?,
+        None => today,
+    })
+}
+
+/// Monday of the week `date` is in.
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Completes the task called `name` among today's static and dynamic tasks.
+fn complete(planner: &mut Planner, name: &str) -> Result<(), Box<dyn Error>> {
+    let today = planner.today();
+    if let Some(i) = planner.statics_on(today).iter().position(|task| {
+        task.get_task().get_name() == name
+            && !planner.is_static_done(today, task.get_task().get_uuid())
+    }) {
+        return Ok(planner.complete_static(today, i)?);
+    }
+
+    let dynamic = planner
+        .dynamic_tasks_on(today)
+        .into_iter()
+        .find(|(_, task)| task.get_task().get_name() == name);
+    match dynamic {
+        Some((i, _)) => Ok(planner.complete_dynamic(i)?),
+        None => Err(format!("There is no task called {name:?} today").into()),
+    }
+}
+
+/// Prints `diagnostic` with the task's name in place of its uuid.
+fn warn(planner: &Planner, diagnostic: &ScheduleDiagnostic) {
+    let mut message = diagnostic.to_string();
+    if let Some(uuid) = diagnostic.task_uuid() {
+        let task = planner
+            .get_dynamic_tasks()
+            .iter()
+            .find(|task| task.get_task().get_uuid() == uuid);
+        if let Some(task) = task {
+            message = message.replace(&uuid.to_string(), task.get_task().get_name());
+        }
+    }
+    eprintln!("warning: {message}");
+}
+
+/// Prints the tasks of `plan` that can't be done by their deadline.
+fn warn_at_risk(planner: &Planner, plan: &DeadlinePlan) {
+    for task in planner.at_risk_in(plan) {
+        if let Some(deadline) = task.get_deadline() {
+            eprintln!(
+                "warning: {} doesn't fit into any day until its deadline {deadline}",
+                task.get_task().get_name()
+            );
+        }
+    }
 }
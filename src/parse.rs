@@ -0,0 +1,214 @@
+//! Parsing of the human friendly time formats used on the command line.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveTime, Weekday};
+
+use crate::{LuniteError, ParseKind, PartOfDay, Rollover, Rule, Strategy, TimeRange};
+
+fn parse_error(kind: ParseKind, s: &str) -> LuniteError {
+    LuniteError::Parse {
+        kind,
+        input: s.to_string(),
+    }
+}
+
+/// Parses a time of day like `14:00`, `9:30`, `14`, `2pm` or `9:30am`.
+pub fn parse_time(s: &str) -> Result<NaiveTime, LuniteError> {
+    let lower = s.trim().to_lowercase();
+    let (time, offset) = if let Some(time) = lower.strip_suffix("am") {
+        (time.trim(), Some(0))
+    } else if let Some(time) = lower.strip_suffix("pm") {
+        (time.trim(), Some(12))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let (hour, minute) = time.split_once(':').unwrap_or((time, "0"));
+    let hour = hour
+        .parse::<u32>()
+        .map_err(|_| parse_error(ParseKind::Time, s))?;
+    let minute = minute
+        .parse::<u32>()
+        .map_err(|_| parse_error(ParseKind::Time, s))?;
+    let hour = match offset {
+        Some(_) if hour == 0 || hour > 12 => return Err(parse_error(ParseKind::Time, s)),
+        Some(offset) => hour % 12 + offset,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0).ok_or_else(|| parse_error(ParseKind::Time, s))
+}
+
+/// Parses a time range like `14:00-16:00`, or `22:00-02:00` for one past midnight.
+pub fn parse_range(s: &str) -> Result<TimeRange, LuniteError> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| parse_error(ParseKind::Range, s))?;
+    let (start, end) = (parse_time(start)?, parse_time(end)?);
+    if start == end {
+        return Err(parse_error(ParseKind::EmptyRange, s));
+    }
+
+    Ok(TimeRange::new(start, end))
+}
+
+/// Parses a duration like `90m`, `2h`, `1h30m` or `1.5h`.
+pub fn parse_duration(s: &str) -> Result<Duration, LuniteError> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+
+    for c in s.trim().to_lowercase().chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'h' | 'm' | 's' => {
+                let n = number
+                    .parse::<f64>()
+                    .map_err(|_| parse_error(ParseKind::Duration, s))?;
+                let seconds = match c {
+                    'h' => n * 3600.0,
+                    'm' => n * 60.0,
+                    _ => n,
+                };
+                // a number too big for an `i64` saturates, and is then too big for a duration
+                total = Duration::try_seconds(seconds.round() as i64)
+                    .and_then(|part| total.checked_add(&part))
+                    .ok_or_else(|| parse_error(ParseKind::Duration, s))?;
+                number.clear();
+            }
+            ' ' => {}
+            _ => return Err(parse_error(ParseKind::Duration, s)),
+        }
+    }
+
+    // a bare number means minutes
+    if !number.is_empty() {
+        let n = number
+            .parse::<i64>()
+            .map_err(|_| parse_error(ParseKind::Duration, s))?;
+        total = Duration::try_minutes(n)
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(|| parse_error(ParseKind::Duration, s))?;
+    }
+    if total <= Duration::zero() {
+        return Err(parse_error(ParseKind::Duration, s));
+    }
+
+    Ok(total)
+}
+
+/// Parses a weekday like `mon` or `Monday`.
+pub fn parse_weekday(s: &str) -> Result<Weekday, LuniteError> {
+    Weekday::from_str(s.trim()).map_err(|_| parse_error(ParseKind::Weekday, s))
+}
+
+/// Parses a date relative to `today`: `today`, `tomorrow`, `yesterday`, a weekday (its next
+/// occurrence, today included), `+3` (days from today) or `2023-04-26`.
+pub fn parse_date(s: &str, today: NaiveDate) -> Result<NaiveDate, LuniteError> {
+    let lower = s.trim().to_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(days) = lower.strip_prefix('+') {
+        let days = days
+            .parse::<u64>()
+            .map_err(|_| parse_error(ParseKind::Date, s))?;
+        return today
+            .checked_add_days(Days::new(days))
+            .ok_or_else(|| parse_error(ParseKind::Date, s));
+    }
+    if let Ok(weekday) = parse_weekday(&lower) {
+        let days =
+            (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        return Ok(today + Duration::days(days as i64));
+    }
+
+    NaiveDate::parse_from_str(&lower, "%Y-%m-%d").map_err(|_| parse_error(ParseKind::Date, s))
+}
+
+impl FromStr for PartOfDay {
+    type Err = LuniteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "morning" => Ok(Self::Morning),
+            "afternoon" => Ok(Self::Afternoon),
+            "evening" => Ok(Self::Evening),
+            "night" => Ok(Self::Night),
+            range => parse_range(range).map(Self::Fixed),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = LuniteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "first-fit" => Ok(Self::FirstFit),
+            "best-fit" => Ok(Self::BestFit),
+            "earliest-deadline-first" | "edf" => Ok(Self::EarliestDeadlineFirst),
+            "spread-evenly" | "spread" => Ok(Self::SpreadEvenly),
+            "optimal" => Ok(Self::Optimal),
+            _ => Err(parse_error(ParseKind::Strategy, s)),
+        }
+    }
+}
+
+impl FromStr for Rollover {
+    type Err = LuniteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "keep" => Ok(Self::Keep),
+            "drop" => Ok(Self::Drop),
+            "next-free-day" | "next" => Ok(Self::NextFreeDay),
+            "overdue" => Ok(Self::Overdue),
+            _ => Err(parse_error(ParseKind::Rollover, s)),
+        }
+    }
+}
+
+/// Parses `daily`, `weekdays`, `weekly`, `biweekly`, `every-3-days`, `every-2-weeks`, or a
+/// weekday of the month like `2nd-tue` or `last-fri`.
+impl FromStr for Rule {
+    type Err = LuniteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase().replace([' ', '_'], "-");
+        match lower.as_str() {
+            "daily" => return Ok(Self::Days(1)),
+            "weekdays" => return Ok(Self::Weekdays),
+            "weekly" => return Ok(Self::Weeks(1)),
+            "biweekly" => return Ok(Self::Weeks(2)),
+            _ => {}
+        }
+
+        let invalid = || parse_error(ParseKind::Rule, s);
+        if let Some(every) = lower.strip_prefix("every-") {
+            let (n, unit) = every.split_once('-').ok_or_else(invalid)?;
+            let n = n.parse::<u32>().map_err(|_| invalid())?;
+            return match unit {
+                "day" | "days" => Ok(Self::Days(n)),
+                "week" | "weeks" => Ok(Self::Weeks(n)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let (nth, weekday) = lower.split_once('-').ok_or_else(invalid)?;
+        let nth = match nth {
+            "1st" | "first" => 1,
+            "2nd" | "second" => 2,
+            "3rd" | "third" => 3,
+            "4th" | "fourth" => 4,
+            "5th" | "fifth" => 5,
+            "last" => -1,
+            _ => return Err(invalid()),
+        };
+        Ok(Self::MonthlyOn(nth, parse_weekday(weekday)?))
+    }
+}
@@ -0,0 +1,141 @@
+//! Tasks repeating on a rule other than every week on the same weekday.
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::StaticTask;
+
+/// How often a [`Recurrence`] repeats, counted from its start date.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum Rule {
+    /// Every `n` days, `Days(1)` being daily.
+    Days(u32),
+    /// Monday to Friday.
+    Weekdays,
+    /// Every `n` weeks on the weekday of the start date, `Weeks(2)` being every other week.
+    Weeks(u32),
+    /// Every month on the `n`th of the weekday, counting from the end of the month if `n` is
+    /// negative, so `MonthlyOn(-1, Weekday::Fri)` is the last Friday.
+    MonthlyOn(i8, Weekday),
+}
+
+impl Rule {
+    /// Whether the rule ever repeats, `Days(0)` or `MonthlyOn(6, _)` don't.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Days(n) | Self::Weeks(n) => *n > 0,
+            Self::Weekdays => true,
+            Self::MonthlyOn(n, _) => (-5..=5).contains(n) && *n != 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Recurrence {
+    rule: Rule,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+    exceptions: Vec<NaiveDate>,
+}
+
+impl Recurrence {
+    pub fn new(rule: Rule, start: NaiveDate) -> Self {
+        Self {
+            rule,
+            start,
+            end: None,
+            exceptions: vec![],
+        }
+    }
+
+    /// Last date the recurrence can happen on.
+    pub fn until(mut self, end: NaiveDate) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Leaves out `date`, even if the rule would repeat on it.
+    pub fn except(mut self, date: NaiveDate) -> Self {
+        self.add_exception(date);
+        self
+    }
+
+    pub fn get_rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn get_start(&self) -> &NaiveDate {
+        &self.start
+    }
+
+    pub fn get_end(&self) -> Option<&NaiveDate> {
+        self.end.as_ref()
+    }
+
+    pub fn get_exceptions(&self) -> &[NaiveDate] {
+        &self.exceptions
+    }
+
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        if date < self.start
+            || matches!(self.end, Some(end) if date > end)
+            || self.exceptions.contains(&date)
+        {
+            return false;
+        }
+
+        let days = (date - self.start).num_days();
+        match self.rule {
+            Rule::Days(n) => n > 0 && days % n as i64 == 0,
+            Rule::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Rule::Weeks(n) => n > 0 && days % (7 * n as i64) == 0,
+            Rule::MonthlyOn(n, weekday) => {
+                let nth = |n: i8| {
+                    NaiveDate::from_weekday_of_month_opt(
+                        date.year(),
+                        date.month(),
+                        weekday,
+                        n as u8,
+                    )
+                };
+                // counting from the end, the last week of the month is the fifth or else the
+                // fourth
+                let n = if n > 0 {
+                    n
+                } else {
+                    let last = if nth(5).is_some() { 5 } else { 4 };
+                    last + 1 + n
+                };
+                n > 0 && nth(n) == Some(date)
+            }
+        }
+    }
+
+    pub(crate) fn add_exception(&mut self, date: NaiveDate) {
+        if !self.exceptions.contains(&date) {
+            self.exceptions.push(date);
+            self.exceptions.sort();
+        }
+    }
+}
+
+/// A static task happening on the dates of its [`Recurrence`] instead of every week.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RecurringTask {
+    pub(crate) task: StaticTask,
+    pub(crate) recurrence: Recurrence,
+}
+
+impl RecurringTask {
+    pub fn new(task: StaticTask, recurrence: Recurrence) -> Self {
+        Self { task, recurrence }
+    }
+
+    pub fn get_task(&self) -> &StaticTask {
+        &self.task
+    }
+
+    pub fn get_recurrence(&self) -> &Recurrence {
+        &self.recurrence
+    }
+}
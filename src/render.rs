@@ -0,0 +1,155 @@
+//! Human friendly rendering of schedules.
+
+use std::fmt::{self, Display, Formatter};
+
+use chrono::Weekday;
+
+use crate::{Schedule, TimeRange};
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}–{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Renders a day's schedule as a timeline, one entry per line:
+///
+/// ```text
+/// 06:00–08:00  task1
+/// 08:00–08:30  free
+/// 08:30–09:00  task6 (part 2/3)
+/// ```
+pub struct DayView<'a, 'b> {
+    schedule: &'b [Schedule<'a>],
+}
+
+impl<'a, 'b> DayView<'a, 'b> {
+    pub fn new(schedule: &'b [Schedule<'a>]) -> Self {
+        Self { schedule }
+    }
+}
+
+impl Display for DayView<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in self.schedule {
+            writeln!(f, "{}  {}", entry.get_time(), label(self.schedule, entry))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the schedules of a week, starting on Monday, side by side in columns.
+pub struct WeekView<'a, 'b> {
+    days: &'b [Vec<Schedule<'a>>],
+    width: usize,
+}
+
+impl<'a, 'b> WeekView<'a, 'b> {
+    pub fn new(days: &'b [Vec<Schedule<'a>>]) -> Self {
+        Self { days, width: 24 }
+    }
+
+    /// Width of a single day's column, longer entries get cut off.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+impl Display for WeekView<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let columns = self
+            .days
+            .iter()
+            .map(|schedule| {
+                schedule
+                    .iter()
+                    .map(|entry| format!("{} {}", entry.get_time(), label(schedule, entry)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut weekday = Weekday::Mon;
+        let header = (0..self.days.len())
+            .map(|_| {
+                let name = format!("{weekday:?}");
+                weekday = weekday.succ();
+                name
+            })
+            .collect::<Vec<_>>();
+        self.write_row(f, header.iter().map(String::as_str))?;
+        let rule = "-".repeat(self.width);
+        self.write_row(f, header.iter().map(|_| rule.as_str()))?;
+
+        for row in 0..rows {
+            self.write_row(
+                f,
+                columns
+                    .iter()
+                    .map(|column| column.get(row).map(String::as_str).unwrap_or("")),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl WeekView<'_, '_> {
+    fn write_row<'c>(
+        &self,
+        f: &mut Formatter<'_>,
+        cells: impl Iterator<Item = &'c str>,
+    ) -> fmt::Result {
+        let row = cells
+            .map(|cell| fit(cell, self.width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(f, "{}", row.trim_end())
+    }
+}
+
+/// Cuts `cell` off or pads it to exactly `width` characters.
+fn fit(cell: &str, width: usize) -> String {
+    if cell.chars().count() > width {
+        let mut cell = cell
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        cell.push('…');
+        cell
+    } else {
+        format!("{cell:width$}")
+    }
+}
+
+/// What an entry of `schedule` is called when shown to the user.
+pub(crate) fn label(schedule: &[Schedule], entry: &Schedule) -> String {
+    match entry {
+        Schedule::Static(task) => task.task.name.clone(),
+        Schedule::DynamicTask(task, _) => task.get_task().name.clone(),
+        Schedule::DynamicPart { of, nth, .. } => {
+            let parts = schedule
+                .iter()
+                .filter(|entry| {
+                    matches!(entry, Schedule::DynamicPart { of: other, .. } if std::ptr::eq(*of, *other))
+                })
+                .count();
+            if parts > 1 {
+                format!("{} (part {nth}/{parts})", of.get_task().name)
+            } else {
+                of.get_task().name.clone()
+            }
+        }
+        Schedule::Buffer(_) => String::from("buffer"),
+        Schedule::Travel(_) => String::from("travel"),
+        Schedule::Break(brk, _) => brk.get_name().to_string(),
+        Schedule::Free(_) => String::from("free"),
+    }
+}
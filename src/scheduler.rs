@@ -0,0 +1,559 @@
+use chrono::{Duration, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+mod optimal;
+
+pub use optimal::Optimal;
+
+use crate::{
+    Config, DynamicTask, LuniteError, Padding, Schedule, ScheduleDiagnostic, StaticTask, Task,
+    TimeRange, TimeRangeSet,
+};
+
+/// Places a day's dynamic tasks into its free time.
+pub trait Scheduler {
+    /// Places `tasks` into the [`Schedule::Free`] ranges of `freetime`. `tasks` come ordered by
+    /// the planner, fixed tasks first and then by priority. Flexible tasks need their padding
+    /// from `config` to fit into the free range along with them.
+    ///
+    /// Every task that couldn't be placed has to end up in [`Placement::unplaced`] as the same
+    /// reference it was passed in as, the planner relies on that when preempting tasks.
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a>;
+}
+
+#[derive(Debug)]
+pub struct Placement<'a> {
+    pub schedule: Vec<Schedule<'a>>,
+    pub diagnostics: Vec<ScheduleDiagnostic>,
+    pub unplaced: Vec<&'a DynamicTask>,
+}
+
+/// The schedulers shipped with the crate, selectable in [`Config`](crate::Config).
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Strategy {
+    #[default]
+    FirstFit,
+    BestFit,
+    EarliestDeadlineFirst,
+    SpreadEvenly,
+    Optimal,
+}
+
+impl Scheduler for Strategy {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        match self {
+            Self::FirstFit => FirstFit.schedule(config, freetime, tasks),
+            Self::BestFit => BestFit.schedule(config, freetime, tasks),
+            Self::EarliestDeadlineFirst => EarliestDeadlineFirst.schedule(config, freetime, tasks),
+            Self::SpreadEvenly => SpreadEvenly.schedule(config, freetime, tasks),
+            Self::Optimal => Optimal.schedule(config, freetime, tasks),
+        }
+    }
+}
+
+/// Puts every task into the first free range that can hold it, splittable tasks fill free
+/// ranges from the start of the day.
+#[derive(Debug, Clone, Copy)]
+pub struct FirstFit;
+
+impl Scheduler for FirstFit {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        place_with(config, freetime, tasks, first_fit, false)
+    }
+}
+
+/// Puts every task into the free range it leaves the least time unused in, keeping large free
+/// ranges around for the tasks that need them.
+#[derive(Debug, Clone, Copy)]
+pub struct BestFit;
+
+impl Scheduler for BestFit {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        place_with(config, freetime, tasks, best_fit, true)
+    }
+}
+
+/// Places flexible tasks whose deadline comes the soonest first, and of those the ones whose
+/// part of day ends the soonest, placing each like [`FirstFit`]. Tasks without a deadline are
+/// due on their date.
+#[derive(Debug, Clone, Copy)]
+pub struct EarliestDeadlineFirst;
+
+impl Scheduler for EarliestDeadlineFirst {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        let mut tasks = tasks.to_vec();
+        tasks.sort_by_key(|task| match task {
+            DynamicTask::Fixed { .. } => None,
+            DynamicTask::Flexible {
+                around,
+                date,
+                deadline,
+                ..
+            } => Some((deadline.unwrap_or(*date), around.window().span().1)),
+        });
+
+        place_with(config, freetime, &tasks, first_fit, false)
+    }
+}
+
+/// Puts every task into the middle of the largest free range, spreading the tasks over the
+/// day instead of packing them together.
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadEvenly;
+
+impl Scheduler for SpreadEvenly {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        place_with(config, freetime, tasks, spread, true)
+    }
+}
+
+/// Picks the range a task of `length` goes into out of all free ranges that can hold it.
+type Pick = fn(&[(usize, TimeRange)], &Duration) -> Option<(usize, TimeRange)>;
+
+fn first_fit(candidates: &[(usize, TimeRange)], length: &Duration) -> Option<(usize, TimeRange)> {
+    candidates
+        .first()
+        .map(|(i, range)| (*i, TimeRange::new(range.start, range.start + *length)))
+}
+
+fn best_fit(candidates: &[(usize, TimeRange)], length: &Duration) -> Option<(usize, TimeRange)> {
+    candidates
+        .iter()
+        .min_by_key(|(_, range)| range.to_duration())
+        .map(|(i, range)| (*i, TimeRange::new(range.start, range.start + *length)))
+}
+
+fn spread(candidates: &[(usize, TimeRange)], length: &Duration) -> Option<(usize, TimeRange)> {
+    candidates
+        .iter()
+        .rev()
+        .max_by_key(|(_, range)| range.to_duration())
+        .map(|(i, range)| {
+            let offset = Duration::minutes(((range.to_duration() - *length) / 2).num_minutes());
+            (
+                *i,
+                TimeRange::new(range.start + offset, range.start + offset + *length),
+            )
+        })
+}
+
+/// Places `tasks` into `freetime` in the given order, letting `pick` choose the range for
+/// every task. With `whole_first` splittable tasks are only split if they don't fit into a
+/// single range inside their part of day.
+fn place_with<'a>(
+    config: &Config,
+    mut freetime: Vec<Schedule<'a>>,
+    tasks: &[&'a DynamicTask],
+    pick: Pick,
+    whole_first: bool,
+) -> Placement<'a> {
+    let mut diagnostics = vec![];
+    let mut unplaced = vec![];
+
+    for &task in tasks {
+        let padding = config.padding(task.get_task());
+        match task {
+            DynamicTask::Fixed {
+                task: StaticTask { time, .. },
+                ..
+            } => {
+                let slot = freetime.iter().position(
+                    |schedule| matches!(schedule, Schedule::Free(range) if time.subset(range)),
+                );
+                match slot {
+                    Some(i) => {
+                        let schedule = Schedule::DynamicTask(task, *time);
+                        occupy_padded(&mut freetime, i, schedule, &padding);
+                    }
+                    None => {
+                        diagnostics.push(ScheduleDiagnostic::Unplaced(LuniteError::TimeNotFree {
+                            task_uuid: task.get_task().uuid,
+                            time: *time,
+                        }));
+                        unplaced.push(task);
+                    }
+                }
+            }
+            DynamicTask::Flexible {
+                task: Task { uuid, .. },
+                length,
+                around,
+                can_split,
+                ..
+            } => {
+                let window = around.window();
+
+                if *can_split {
+                    if whole_first && task.fixed_split(length, 1).is_ok() {
+                        if let Some((i, range)) =
+                            find_slot(&freetime, Some(&window), length, &padding, pick)
+                        {
+                            let part = Schedule::DynamicPart {
+                                part: Box::new(task.fixed_split(length, 1).unwrap()),
+                                of: task,
+                                nth: 1,
+                                time: range,
+                            };
+                            occupy_padded(&mut freetime, i, part, &padding);
+                            continue;
+                        }
+                    }
+
+                    let free = free_ranges(&freetime);
+                    let windows = free_windows(&freetime);
+                    let gap = config.get_part_gap();
+                    match split(&free, &windows, task, &window, &padding, gap) {
+                        Some((parts, outside)) => {
+                            if outside {
+                                diagnostics.push(ScheduleDiagnostic::OutsidePartOfDay {
+                                    task_uuid: *uuid,
+                                    around: *around,
+                                });
+                            }
+                            place_parts(config, &mut freetime, task, &parts, &padding);
+                        }
+                        None => {
+                            diagnostics.push(ScheduleDiagnostic::Unplaced(
+                                LuniteError::NotEnoughFreeTime {
+                                    task_uuid: *uuid,
+                                    needed: *length,
+                                    available: free.to_duration(),
+                                },
+                            ));
+                            unplaced.push(task);
+                        }
+                    }
+
+                    continue;
+                }
+
+                let slot =
+                    find_slot(&freetime, Some(&window), length, &padding, pick).or_else(|| {
+                        let slot = find_slot(&freetime, None, length, &padding, pick);
+                        if slot.is_some() {
+                            diagnostics.push(ScheduleDiagnostic::OutsidePartOfDay {
+                                task_uuid: *uuid,
+                                around: *around,
+                            });
+                        }
+                        slot
+                    });
+                match slot {
+                    Some((i, range)) => {
+                        let schedule = Schedule::DynamicTask(task, range);
+                        occupy_padded(&mut freetime, i, schedule, &padding);
+                    }
+                    None => {
+                        let longest = freetime
+                            .iter()
+                            .filter_map(|schedule| match schedule {
+                                Schedule::Free(range) => Some(range.to_duration()),
+                                _ => None,
+                            })
+                            .max()
+                            .unwrap_or_else(Duration::zero);
+                        diagnostics.push(ScheduleDiagnostic::Unplaced(
+                            LuniteError::NotEnoughFreeTime {
+                                task_uuid: *uuid,
+                                needed: *length,
+                                available: longest,
+                            },
+                        ));
+                        unplaced.push(task);
+                    }
+                }
+            }
+        };
+    }
+
+    Placement {
+        schedule: freetime,
+        diagnostics,
+        unplaced,
+    }
+}
+
+/// Like [`occupy`], also taking as much of `padding` around `schedule` as the free range at
+/// `i` holds as buffers and travel.
+fn occupy_padded<'a>(
+    freetime: &mut Vec<Schedule<'a>>,
+    i: usize,
+    schedule: Schedule<'a>,
+    padding: &Padding,
+) {
+    let Schedule::Free(free) = freetime[i] else {
+        unreachable!()
+    };
+    let core = schedule.get_time();
+    let padding = fit(&free, &core, padding);
+
+    // every entry after the first is taken from what's left after the previous one
+    let mut first = occupy(freetime, i, schedule);
+    if padding.after > Duration::zero() {
+        let after = TimeRange::new(core.end, core.end + padding.after);
+        occupy(freetime, first + 1, Schedule::Buffer(after));
+    }
+    if padding.before > Duration::zero() {
+        let before = TimeRange::new(core.start - padding.before, core.start);
+        first = occupy(freetime, first - 1, Schedule::Buffer(before));
+    }
+    if padding.travel > Duration::zero() {
+        let start = core.start - padding.before;
+        let travel = TimeRange::new(start - padding.travel, start);
+        occupy(freetime, first - 1, Schedule::Travel(travel));
+    }
+}
+
+/// How long it is from `from` to `to`, going past midnight if `to` is earlier.
+fn between(from: NaiveTime, to: NaiveTime) -> Duration {
+    let duration = to - from;
+    if duration < Duration::zero() {
+        duration + Duration::days(1)
+    } else {
+        duration
+    }
+}
+
+/// The part of `free` a task can be in with all of its `padding` still inside of `free`.
+fn shrink(free: &TimeRange, padding: &Padding) -> Option<TimeRange> {
+    let lead = padding.travel + padding.before;
+    if free.to_duration() <= lead + padding.after {
+        return None;
+    }
+    Some(TimeRange::new(free.start + lead, free.end - padding.after))
+}
+
+/// As much of `padding` as fits between `core` and the ends of `free`, the buffers being kept
+/// before the travel.
+fn fit(free: &TimeRange, core: &TimeRange, padding: &Padding) -> Padding {
+    let lead = between(free.start, core.start);
+    let before = padding.before.min(lead);
+    Padding {
+        travel: padding.travel.min(lead - before),
+        before,
+        after: padding.after.min(between(core.end, free.end)),
+    }
+}
+
+/// Replaces the free range at `i` with `schedule`, keeping whatever is left of the free range
+/// on either side of it. Returns the new index of `schedule`.
+fn occupy<'a>(freetime: &mut Vec<Schedule<'a>>, i: usize, schedule: Schedule<'a>) -> usize {
+    let Schedule::Free(free) = freetime[i] else {
+        unreachable!()
+    };
+    let range = schedule.get_time();
+
+    freetime[i] = schedule;
+    let mut i = i;
+    if free.start != range.start {
+        freetime.insert(i, Schedule::Free(TimeRange::new(free.start, range.start)));
+        i += 1;
+    }
+    if free.end != range.end {
+        freetime.insert(i + 1, Schedule::Free(TimeRange::new(range.end, free.end)));
+    }
+    i
+}
+
+/// Lets `pick` choose a free range that can hold `length` along with `padding`, only looking
+/// inside `within` if given.
+fn find_slot(
+    freetime: &[Schedule],
+    within: Option<&TimeRange>,
+    length: &Duration,
+    padding: &Padding,
+    pick: Pick,
+) -> Option<(usize, TimeRange)> {
+    let candidates = freetime
+        .iter()
+        .enumerate()
+        .filter_map(|(i, schedule)| {
+            let Schedule::Free(range) = schedule else {
+                return None;
+            };
+            let range = shrink(range, padding)?;
+            let range = match within {
+                Some(within) => range.intersection(within)?,
+                None => range,
+            };
+            if range.to_duration() >= *length {
+                Some((i, range))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    pick(&candidates, length)
+}
+
+/// The free ranges of `freetime` in the order of the day. Time repeated on a day the clocks
+/// are set back on is in two of them, see [`Config::repeated_time`].
+fn free_windows(freetime: &[Schedule]) -> Vec<TimeRange> {
+    freetime
+        .iter()
+        .filter_map(|schedule| match schedule {
+            Schedule::Free(range) => Some(*range),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The free time of `freetime`.
+fn free_ranges(freetime: &[Schedule]) -> TimeRangeSet {
+    free_windows(freetime).into_iter().collect()
+}
+
+/// What's left of `free` in `windows` from [`free_windows`], in the order of the day.
+fn left_within(free: &TimeRangeSet, windows: &[TimeRange]) -> Vec<TimeRange> {
+    windows
+        .iter()
+        .flat_map(|window| free.within(window))
+        .collect()
+}
+
+/// Splits `task` into parts taken from the start of the ranges of `free` in `windows`, filling
+/// the ones inside `window` first, keeping to the task's chunk limits, `padding` around every
+/// part and at least `gap` between the parts. Returns the parts and whether any of them is
+/// outside of `window`, or `None` if the parts can't make up the task.
+fn split(
+    free: &TimeRangeSet,
+    windows: &[TimeRange],
+    task: &DynamicTask,
+    window: &TimeRange,
+    padding: &Padding,
+    gap: Duration,
+) -> Option<(Vec<TimeRange>, bool)> {
+    let DynamicTask::Flexible { length, .. } = task else {
+        return None;
+    };
+    let (min, max, max_parts) = task.get_chunks();
+    let min = min.copied().unwrap_or_else(Duration::zero);
+
+    // how much of `available` the next part can take with `left` still to place
+    let chunk = |available: Duration, left: Duration, parts: usize| {
+        let mut chunk = available.min(left);
+        if let Some(max) = max {
+            chunk = chunk.min(*max);
+        }
+        if max_parts.is_some_and(|max| parts + 1 >= max) && chunk < left {
+            return None;
+        }
+        // never leave a rest too short to be a part of its own
+        if chunk < left && left - chunk < min {
+            chunk = left - min;
+        }
+        (chunk >= min && chunk > Duration::zero()).then_some(chunk)
+    };
+
+    let mut free = free.clone();
+    let mut parts = vec![];
+    let mut left = *length;
+    for within in [Some(window), None] {
+        while left > Duration::zero() {
+            let next = left_within(&free, windows).iter().find_map(|free| {
+                let range = shrink(free, padding)?;
+                let range = match within {
+                    Some(within) => range.intersection(within)?,
+                    None => range,
+                };
+                let chunk = chunk(range.to_duration(), left, parts.len())?;
+                Some(TimeRange::new(range.start, range.start + chunk))
+            });
+            let Some(part) = next else {
+                break;
+            };
+
+            take(&mut free, windows, &part, padding);
+            // the gap is only kept in the free range of the part, on a day the clocks go
+            // back the wall clock time after it can come again in the next one
+            let around = TimeRangeSet::from(TimeRange::new(part.start - gap, part.end + gap));
+            if let Some(window) = windows.iter().find(|window| part.subset(window)) {
+                for range in around.within(window) {
+                    free.remove(range);
+                }
+            }
+            parts.push(part);
+            left -= part.to_duration();
+        }
+    }
+
+    if left > Duration::zero() {
+        return None;
+    }
+    let outside = parts.iter().any(|part| !part.subset(window));
+    Some((parts, outside))
+}
+
+/// Takes `core` and the part of `padding` around it that fits out of `free`, `core` has to be
+/// inside one of its ranges in `windows`.
+fn take(free: &mut TimeRangeSet, windows: &[TimeRange], core: &TimeRange, padding: &Padding) {
+    let Some(range) = left_within(free, windows)
+        .into_iter()
+        .find(|range| core.subset(range))
+    else {
+        return;
+    };
+
+    let padding = fit(&range, core, padding);
+    free.remove(TimeRange::new(
+        core.start - padding.before - padding.travel,
+        core.end + padding.after,
+    ));
+}
+
+/// Puts the `parts` of `task` from [`split`] into the free ranges holding them, numbered in
+/// the order they happen in the day of `config`.
+fn place_parts<'a>(
+    config: &Config,
+    freetime: &mut Vec<Schedule<'a>>,
+    task: &'a DynamicTask,
+    parts: &[TimeRange],
+    padding: &Padding,
+) {
+    let mut parts = parts.to_vec();
+    parts.sort_by_key(|range| config.offset(range.start));
+    for (n, range) in parts.iter().enumerate() {
+        let part = Schedule::DynamicPart {
+            part: Box::new(task.fixed_split(&range.to_duration(), n + 1).unwrap()),
+            of: task,
+            nth: n + 1,
+            time: *range,
+        };
+        let i = freetime
+            .iter()
+            .position(|entry| matches!(entry, Schedule::Free(free) if range.subset(free)))
+            .expect("parts are taken from free time");
+        occupy_padded(freetime, i, part, padding);
+    }
+}
@@ -0,0 +1,314 @@
+use std::cmp::Reverse;
+
+use chrono::Duration;
+
+use super::{
+    free_ranges, free_windows, left_within, occupy_padded, place_parts, shrink, split, take,
+    Placement, Scheduler,
+};
+use crate::{
+    Config, DynamicTask, LuniteError, Padding, Schedule, ScheduleDiagnostic, StaticTask, TimeRange,
+    TimeRangeSet,
+};
+
+/// How many placements [`Optimal`] looks at before settling for the best one found so far.
+const SEARCH_LIMIT: usize = 20_000;
+
+/// Searches through the ways of placing the tasks for the one placing the most time weighted by
+/// priority, preferring placements inside of the tasks' parts of day. Unlike the greedy
+/// schedulers it moves tasks around to make room, at the cost of being slower.
+///
+/// Every task is tried at the start and the end of every free range and of its part of day
+/// within it, splittable tasks are split like the greedy schedulers split them.
+#[derive(Debug, Clone, Copy)]
+pub struct Optimal;
+
+impl Scheduler for Optimal {
+    fn schedule<'a>(
+        &self,
+        config: &Config,
+        mut freetime: Vec<Schedule<'a>>,
+        tasks: &[&'a DynamicTask],
+    ) -> Placement<'a> {
+        let free = free_ranges(&freetime);
+        let windows = free_windows(&freetime);
+
+        // tasks with fewer ways to be placed go first, they constrain the rest the most
+        let lowest = tasks
+            .iter()
+            .map(|task| task.get_priority())
+            .min()
+            .unwrap_or(0);
+        let mut order = tasks
+            .iter()
+            .map(|task| (*task, weight(task, lowest)))
+            .collect::<Vec<_>>();
+        order.sort_by_key(|(task, weight)| {
+            let rank = match task {
+                DynamicTask::Fixed { .. } => 0,
+                DynamicTask::Flexible {
+                    can_split: false, ..
+                } => 1,
+                DynamicTask::Flexible { .. } => 2,
+            };
+            (rank, Reverse(*weight))
+        });
+
+        let mut search = Search {
+            remaining: order
+                .iter()
+                .rev()
+                .scan(0, |total, (_, weight)| {
+                    *total += weight;
+                    Some(*total)
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect(),
+            order: &order,
+            windows: &windows,
+            choices: vec![],
+            best: None,
+            nodes: 0,
+        };
+        search.run(config, free, (0, Reverse(0)));
+        let choices = search
+            .best
+            .map(|(_, choices)| choices)
+            .unwrap_or_else(|| vec![None; order.len()]);
+
+        let mut diagnostics = vec![];
+        for ((task, _), choice) in order.iter().zip(&choices) {
+            let Some(choice) = choice else {
+                continue;
+            };
+            if choice.outside {
+                if let DynamicTask::Flexible {
+                    task: inner,
+                    around,
+                    ..
+                } = task
+                {
+                    diagnostics.push(ScheduleDiagnostic::OutsidePartOfDay {
+                        task_uuid: inner.uuid,
+                        around: *around,
+                    });
+                }
+            }
+
+            match task {
+                DynamicTask::Flexible {
+                    can_split: true, ..
+                } => place_parts(config, &mut freetime, task, &choice.ranges, &choice.padding),
+                _ => {
+                    let range = choice.ranges[0];
+                    let i = freetime
+                        .iter()
+                        .position(
+                            |entry| matches!(entry, Schedule::Free(free) if range.subset(free)),
+                        )
+                        .expect("the search only picks free time");
+                    let schedule = Schedule::DynamicTask(task, range);
+                    occupy_padded(&mut freetime, i, schedule, &choice.padding);
+                }
+            }
+        }
+
+        let free = free_ranges(&freetime);
+        let windows = free_windows(&freetime);
+        let mut unplaced = vec![];
+        for &task in tasks {
+            let i = order
+                .iter()
+                .position(|(other, _)| std::ptr::eq(*other, task))
+                .unwrap();
+            if choices[i].is_some() {
+                continue;
+            }
+
+            diagnostics.push(ScheduleDiagnostic::Unplaced(match task {
+                DynamicTask::Fixed {
+                    task: StaticTask { task, time },
+                    ..
+                } => LuniteError::TimeNotFree {
+                    task_uuid: task.uuid,
+                    time: *time,
+                },
+                DynamicTask::Flexible {
+                    task,
+                    length,
+                    can_split,
+                    ..
+                } => LuniteError::NotEnoughFreeTime {
+                    task_uuid: task.uuid,
+                    needed: *length,
+                    available: if *can_split {
+                        free.to_duration()
+                    } else {
+                        windows
+                            .iter()
+                            .map(TimeRange::to_duration)
+                            .max()
+                            .unwrap_or_else(Duration::zero)
+                    },
+                },
+            }));
+            unplaced.push(task);
+        }
+
+        Placement {
+            schedule: freetime,
+            diagnostics,
+            unplaced,
+        }
+    }
+}
+
+/// Where a task goes, `outside` if (partly) outside of its part of day.
+#[derive(Debug, Clone)]
+struct Choice {
+    ranges: Vec<TimeRange>,
+    outside: bool,
+    padding: Padding,
+}
+
+/// Weighted time placed and how many tasks are outside of their part of day.
+type Score = (i64, Reverse<usize>);
+
+struct Search<'t, 'a> {
+    order: &'t [(&'a DynamicTask, i64)],
+    /// The free ranges the free time is in, see [`free_windows`].
+    windows: &'t [TimeRange],
+    /// Weight of all tasks from the index on, the most the rest of the search can add.
+    remaining: Vec<i64>,
+    choices: Vec<Option<Choice>>,
+    best: Option<(Score, Vec<Option<Choice>>)>,
+    nodes: usize,
+}
+
+impl Search<'_, '_> {
+    fn run(&mut self, config: &Config, free: TimeRangeSet, score: Score) {
+        self.nodes += 1;
+        let depth = self.choices.len();
+        if let Some((best, _)) = &self.best {
+            let bound = (score.0 + self.remaining.get(depth).unwrap_or(&0), score.1);
+            if bound <= *best || self.nodes > SEARCH_LIMIT {
+                return;
+            }
+        }
+        let Some(&(task, weight)) = self.order.get(depth) else {
+            self.best = Some((score, self.choices.clone()));
+            return;
+        };
+
+        let padding = config.padding(task.get_task());
+        for choice in candidates(task, &free, self.windows, padding, config.get_part_gap()) {
+            let mut left = free.clone();
+            for range in &choice.ranges {
+                take(&mut left, self.windows, range, &choice.padding);
+            }
+            let outside = score.1 .0 + choice.outside as usize;
+            self.choices.push(Some(choice));
+            self.run(config, left, (score.0 + weight, Reverse(outside)));
+            self.choices.pop();
+        }
+
+        self.choices.push(None);
+        self.run(config, free, score);
+        self.choices.pop();
+    }
+}
+
+/// How much placing `task` is worth, its length in minutes weighted by how its priority
+/// compares to the `lowest` one.
+fn weight(task: &DynamicTask, lowest: i32) -> i64 {
+    let minutes = match task {
+        DynamicTask::Fixed {
+            task: StaticTask { time, .. },
+            ..
+        } => time.to_duration(),
+        DynamicTask::Flexible { length, .. } => *length,
+    }
+    .num_minutes()
+    .max(1);
+
+    (task.get_priority() as i64 - lowest as i64 + 1) * minutes
+}
+
+/// The ways `task` can be placed into `free` with `padding` around it and `gap` between its
+/// parts, the ones inside of its part of day first.
+fn candidates(
+    task: &DynamicTask,
+    free: &TimeRangeSet,
+    windows: &[TimeRange],
+    padding: Padding,
+    gap: Duration,
+) -> Vec<Choice> {
+    let ranges = left_within(free, windows);
+    let (length, window, can_split) = match task {
+        DynamicTask::Fixed {
+            task: StaticTask { time, .. },
+            ..
+        } => {
+            return if ranges.iter().any(|free| time.subset(free)) {
+                vec![Choice {
+                    ranges: vec![*time],
+                    outside: false,
+                    padding,
+                }]
+            } else {
+                vec![]
+            };
+        }
+        DynamicTask::Flexible {
+            length,
+            around,
+            can_split,
+            ..
+        } => (*length, around.window(), *can_split),
+    };
+
+    if can_split {
+        return split(free, windows, task, &window, &padding, gap)
+            .map(|(ranges, outside)| Choice {
+                ranges,
+                outside,
+                padding,
+            })
+            .into_iter()
+            .collect();
+    }
+
+    let mut choices: Vec<Choice> = vec![];
+    for inside in [true, false] {
+        for free in &ranges {
+            let Some(range) = shrink(free, &padding) else {
+                continue;
+            };
+            let range = if inside {
+                match range.intersection(&window) {
+                    Some(range) => range,
+                    None => continue,
+                }
+            } else {
+                range
+            };
+            if range.to_duration() < length {
+                continue;
+            }
+
+            for start in [range.start, range.end - length] {
+                let time = TimeRange::new(start, start + length);
+                if choices.iter().all(|choice| choice.ranges[0] != time) {
+                    choices.push(Choice {
+                        ranges: vec![time],
+                        outside: !time.subset(&window),
+                        padding,
+                    });
+                }
+            }
+        }
+    }
+    choices
+}
@@ -0,0 +1,150 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{LuniteError, Planner};
+
+/// Version of the planner file format written by [`Planner::save`].
+pub const PLANNER_VERSION: u64 = 2;
+
+/// Upgrades the `planner` object of a file by one version, `MIGRATIONS[n]` takes a version `n`
+/// planner to version `n + 1`. Whenever the data model changes in a way serde defaults can't
+/// cover, bump [`PLANNER_VERSION`] and add a migration here.
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; PLANNER_VERSION as usize] = [
+    // version 0 files are a bare planner without the version envelope
+    Ok,
+    move_static_done,
+];
+
+/// Version 1 kept completions of static tasks on the weekday, hiding them every week after.
+/// They're moved to the date they were completed on.
+fn move_static_done(mut planner: Value) -> Result<Value, String> {
+    let days = planner
+        .get_mut("days")
+        .and_then(Value::as_array_mut)
+        .ok_or("days are missing")?;
+    let mut done = vec![];
+    for day in days {
+        let day = day.as_object_mut().ok_or("day isn't an object")?;
+        if let Some(Value::Array(static_done)) = day.remove("static_done") {
+            done.extend(static_done);
+        }
+        day.remove("dynamic_tasks");
+    }
+
+    let calendar = planner
+        .as_object_mut()
+        .ok_or("planner isn't an object")?
+        .entry("calendar")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or("calendar isn't an object")?;
+    for completion in done {
+        let date = completion
+            .get(1)
+            .and_then(Value::as_str)
+            .and_then(|done_at| done_at.get(..10))
+            .ok_or("completion has no time")?
+            .to_string();
+        let plan = calendar
+            .entry(date)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or("day plan isn't an object")?;
+        plan.entry("done")
+            .or_insert_with(|| Value::Array(vec![]))
+            .as_array_mut()
+            .ok_or("done isn't an array")?
+            .push(completion);
+    }
+
+    Ok(planner)
+}
+
+#[derive(Serialize)]
+struct PlannerFileRef<'a> {
+    version: u64,
+    planner: &'a Planner,
+}
+
+#[derive(Deserialize)]
+struct PlannerFile {
+    version: u64,
+    planner: Value,
+}
+
+impl Planner {
+    /// Loads a planner saved with [`Planner::save`], migrating files written by older
+    /// versions.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LuniteError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(io_error(path))?;
+
+        Self::from_json(&contents)
+    }
+
+    /// Saves the planner to `path`. The file is written next to `path` first and then renamed
+    /// over it, so a crash midway never leaves a half written planner behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LuniteError> {
+        let path = path.as_ref();
+        let file_name = path.file_name().ok_or_else(|| {
+            io_error(path)(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "isn't a file path",
+            ))
+        })?;
+        let tmp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+        let mut file = fs::File::create(&tmp).map_err(io_error(&tmp))?;
+        file.write_all(self.to_json()?.as_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(io_error(&tmp))?;
+        fs::rename(&tmp, path).map_err(io_error(path))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, LuniteError> {
+        let value = serde_json::from_str::<Value>(json)?;
+        let PlannerFile {
+            version,
+            mut planner,
+        } = if value.get("version").is_some() {
+            serde_json::from_value(value)?
+        } else {
+            PlannerFile {
+                version: 0,
+                planner: value,
+            }
+        };
+
+        if version > PLANNER_VERSION {
+            return Err(LuniteError::UnsupportedVersion(version));
+        }
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            planner = migration(planner).map_err(|message| LuniteError::Migration {
+                version: version as u64,
+                message,
+            })?;
+        }
+
+        Ok(serde_json::from_value(planner)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, LuniteError> {
+        Ok(serde_json::to_string_pretty(&PlannerFileRef {
+            version: PLANNER_VERSION,
+            planner: self,
+        })?)
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> LuniteError + '_ {
+    move |source| LuniteError::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
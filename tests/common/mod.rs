@@ -0,0 +1,40 @@
+#![allow(dead_code)]
+
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use lunite::{Config, FixedClock, Planner, Schedule, TimeRange};
+
+/// Monday the planner's clock is set to.
+pub fn monday() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 5, 1).unwrap()
+}
+
+pub fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+pub fn range(start: (u32, u32), end: (u32, u32)) -> TimeRange {
+    TimeRange::new(time(start.0, start.1), time(end.0, end.1))
+}
+
+/// Planner for a UTC day from 06:00 to 22:00, its clock stuck at 08:00 on [`monday`].
+pub fn planner() -> Planner {
+    planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::UTC))
+}
+
+/// Planner with its clock stuck at 08:00 UTC on [`monday`].
+pub fn planner_with(config: Config) -> Planner {
+    let now = Utc.from_utc_datetime(&monday().and_time(time(8, 0)));
+    Planner::new(config).clock(FixedClock(now))
+}
+
+/// The free ranges of `schedule`.
+pub fn free(schedule: &[Schedule]) -> Vec<TimeRange> {
+    schedule
+        .iter()
+        .filter_map(|entry| match entry {
+            Schedule::Free(range) => Some(*range),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,444 @@
+mod common;
+
+use chrono::{Duration, Weekday};
+use chrono_tz::Tz;
+use common::{free, monday, planner, planner_with, range, time};
+use lunite::{
+    Break, Config, LuniteError, Recurrence, RecurringTask, Rule, Schedule, StaticTask, Task,
+    TimeRange,
+};
+use proptest::prelude::*;
+
+fn task(name: &str, time: lunite::TimeRange) -> StaticTask {
+    StaticTask::new(Task::new(name.to_string(), String::new()), time)
+}
+
+#[test]
+fn empty_day_is_free_from_wake_to_bed() {
+    let planner = planner();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(monday())),
+        [range((6, 0), (22, 0))]
+    );
+}
+
+#[test]
+fn single_task_leaves_time_around_it() {
+    let mut planner = planner();
+    planner
+        .add_static(0, task("work", range((9, 0), (12, 0))))
+        .unwrap();
+
+    let schedule = planner.get_freetime_for(monday());
+    assert_eq!(
+        free(&schedule),
+        [range((6, 0), (9, 0)), range((12, 0), (22, 0))]
+    );
+    assert!(matches!(schedule[1], Schedule::Static(_)));
+}
+
+#[test]
+fn tasks_at_wake_and_bed_time_leave_no_empty_ranges() {
+    let mut planner = planner();
+    planner
+        .add_static(0, task("run", range((6, 0), (7, 0))))
+        .unwrap();
+    planner
+        .add_static(0, task("read", range((21, 0), (22, 0))))
+        .unwrap();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(monday())),
+        [range((7, 0), (21, 0))]
+    );
+}
+
+#[test]
+fn buffers_and_travel_are_kept_around_tasks() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .buffer(Duration::minutes(15))
+        .travel(Duration::minutes(30));
+    let mut planner = planner_with(config);
+    let at = |name: &str, location: &str, time| {
+        let task = Task::new(name.to_string(), String::new()).location(location.to_string());
+        StaticTask::new(task, time)
+    };
+    planner
+        .add_static(0, at("work", "office", range((9, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_static(0, at("gym", "gym", range((13, 0), (14, 0))))
+        .unwrap();
+    let call =
+        Task::new("call".to_string(), String::new()).padding(Duration::zero(), Duration::zero());
+    planner
+        .add_static(0, StaticTask::new(call, range((15, 0), (16, 0))))
+        .unwrap();
+
+    // there's nowhere to travel from to work, the call has no padding of its own
+    let schedule = planner.get_freetime_for(monday());
+    let kinds = schedule
+        .iter()
+        .map(|entry| match entry {
+            Schedule::Buffer(range) => ("buffer", *range),
+            Schedule::Travel(range) => ("travel", *range),
+            Schedule::Free(range) => ("free", *range),
+            entry => ("task", entry.get_time()),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("free", range((6, 0), (8, 45))),
+            ("buffer", range((8, 45), (9, 0))),
+            ("task", range((9, 0), (12, 0))),
+            ("buffer", range((12, 0), (12, 15))),
+            ("travel", range((12, 15), (12, 45))),
+            ("buffer", range((12, 45), (13, 0))),
+            ("task", range((13, 0), (14, 0))),
+            ("buffer", range((14, 0), (14, 15))),
+            ("free", range((14, 15), (15, 0))),
+            ("task", range((15, 0), (16, 0))),
+            ("free", range((16, 0), (22, 0))),
+        ]
+    );
+}
+
+#[test]
+fn breaks_move_around_static_tasks() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .protect(Break::at(
+            "lunch".to_string(),
+            range((12, 0), (13, 0)),
+            Duration::minutes(30),
+        ))
+        .protect(Break::every(
+            "pause".to_string(),
+            Duration::hours(4),
+            Duration::minutes(15),
+        ));
+    let mut planner = planner_with(config);
+    planner
+        .add_static(0, task("meeting", range((11, 0), (12, 20))))
+        .unwrap();
+
+    let schedule = planner.get_freetime_for(monday());
+    let breaks = schedule
+        .iter()
+        .filter_map(|entry| match entry {
+            Schedule::Break(brk, time) => Some((brk.get_name(), *time)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // pauses only come between tasks placed into the free time
+    assert_eq!(breaks, [("lunch", range((12, 20), (13, 20)))]);
+    assert_eq!(
+        free(&schedule),
+        [range((6, 0), (11, 0)), range((13, 20), (22, 0))]
+    );
+}
+
+#[test]
+fn overlapping_tasks_are_free_after_the_last_one_ends() {
+    let mut planner = planner();
+    planner
+        .add_static(0, task("long", range((9, 0), (14, 0))))
+        .unwrap();
+    planner
+        .add_static_allow_overlap(0, task("short", range((10, 0), (11, 0))))
+        .unwrap();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(monday())),
+        [range((6, 0), (9, 0)), range((14, 0), (22, 0))]
+    );
+}
+
+#[test]
+fn done_and_skipped_tasks_are_free() {
+    let mut planner = planner();
+    planner
+        .add_static(0, task("work", range((9, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_static(1, task("work", range((9, 0), (12, 0))))
+        .unwrap();
+    planner.complete_static(monday(), 0).unwrap();
+    planner
+        .skip_static(monday() + Duration::days(1), 0)
+        .unwrap();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(monday())),
+        [range((6, 0), (22, 0))]
+    );
+    assert_eq!(
+        free(&planner.get_freetime_for(monday() + Duration::days(1))),
+        [range((6, 0), (22, 0))]
+    );
+    // both only apply to their date, the week after the task is back
+    for date in [monday() + Duration::days(7), monday() + Duration::days(8)] {
+        assert_eq!(
+            free(&planner.get_freetime_for(date)),
+            [range((6, 0), (9, 0)), range((12, 0), (22, 0))]
+        );
+    }
+
+    let history = planner.completions_between(monday(), monday() + Duration::days(6));
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].date, monday());
+    assert_eq!(history[0].task.get_name(), "work");
+    assert!(planner
+        .completions_between(monday() + Duration::days(1), monday() + Duration::days(6))
+        .is_empty());
+}
+
+#[test]
+fn recurring_tasks_only_take_time_on_their_dates() {
+    let mut planner = planner();
+    let recurrence = Recurrence::new(Rule::Weeks(2), monday());
+    planner
+        .add_recurring(RecurringTask::new(
+            task("sync", range((10, 0), (11, 0))),
+            recurrence,
+        ))
+        .unwrap();
+
+    assert_eq!(free(&planner.get_freetime_for(monday())).len(), 2);
+    assert_eq!(
+        free(&planner.get_freetime_for(monday() + Duration::weeks(1))).len(),
+        1
+    );
+    assert_eq!(
+        free(&planner.get_freetime_for(monday() + Duration::weeks(2))).len(),
+        2
+    );
+
+    let monthly = Recurrence::new(Rule::MonthlyOn(-1, Weekday::Fri), monday());
+    assert!(monthly.occurs_on(chrono::NaiveDate::from_ymd_opt(2023, 5, 26).unwrap()));
+    assert!(!monthly.occurs_on(chrono::NaiveDate::from_ymd_opt(2023, 5, 19).unwrap()));
+    // May 2023 has five Wednesdays
+    let may = |day| chrono::NaiveDate::from_ymd_opt(2023, 5, day).unwrap();
+    for (n, day) in [(1, 3), (2, 10), (5, 31), (-1, 31), (-2, 24), (-5, 3)] {
+        let monthly = Recurrence::new(Rule::MonthlyOn(n, Weekday::Wed), monday());
+        assert!(monthly.occurs_on(may(day)), "{n}");
+        assert!(!monthly.occurs_on(may(day) + Duration::weeks(1)), "{n}");
+    }
+}
+
+#[test]
+fn recurring_tasks_cant_overlap_other_static_tasks() {
+    let mut planner = planner();
+    planner
+        .add_static(2, task("gym", range((10, 0), (11, 0))))
+        .unwrap();
+    let sync = |rule, time| RecurringTask::new(task("sync", time), Recurrence::new(rule, monday()));
+
+    // every other day meets the gym on the wednesday
+    assert!(matches!(
+        planner.add_recurring(sync(Rule::Days(2), range((10, 30), (11, 30)))),
+        Err(LuniteError::Conflict { name, .. }) if name == "gym"
+    ));
+    // every third day only gets to a wednesday the week after
+    assert!(matches!(
+        planner.add_recurring(sync(Rule::Days(3), range((10, 30), (11, 30)))),
+        Err(LuniteError::Conflict { .. })
+    ));
+    planner
+        .add_recurring(sync(Rule::Weeks(1), range((10, 30), (11, 30))))
+        .unwrap();
+    // other recurring tasks count too
+    assert!(matches!(
+        planner.add_recurring(sync(Rule::Weekdays, range((11, 0), (12, 0)))),
+        Err(LuniteError::Conflict { .. })
+    ));
+    planner
+        .add_recurring_allow_overlap(sync(Rule::Weekdays, range((11, 0), (12, 0))))
+        .unwrap();
+    assert_eq!(planner.get_recurring().len(), 2);
+}
+
+#[test]
+fn recurring_tasks_are_within_the_day_of_every_date() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .weekends(time(8, 0), time(22, 0));
+    let mut planner = planner_with(config);
+    let run = |rule| {
+        RecurringTask::new(
+            task("run", range((7, 0), (7, 30))),
+            Recurrence::new(rule, monday()),
+        )
+    };
+
+    // the saturday starts later than the monday the task starts on
+    assert!(matches!(
+        planner.add_recurring_allow_overlap(run(Rule::Days(1))),
+        Err(LuniteError::OutsideOfDay { day, .. }) if day == range((8, 0), (22, 0))
+    ));
+    planner
+        .add_recurring_allow_overlap(run(Rule::Weekdays))
+        .unwrap();
+}
+
+#[test]
+fn static_tasks_cant_overlap_recurring_tasks() {
+    let mut planner = planner();
+    let sync = |rule, time| RecurringTask::new(task("sync", time), Recurrence::new(rule, monday()));
+    planner
+        .add_recurring(sync(Rule::Weeks(1), range((10, 30), (11, 30))))
+        .unwrap();
+    planner
+        .add_recurring(sync(Rule::Weekdays, range((12, 0), (13, 0))))
+        .unwrap();
+
+    assert!(matches!(
+        planner.add_static(0, task("gym", range((10, 0), (11, 0)))),
+        Err(LuniteError::Conflict { name, .. }) if name == "sync"
+    ));
+    // the weekly sync is only on mondays
+    planner
+        .add_static(1, task("gym", range((10, 0), (11, 0))))
+        .unwrap();
+    assert!(matches!(
+        planner.add_static(4, task("lunch", range((12, 30), (13, 30)))),
+        Err(LuniteError::Conflict { .. })
+    ));
+    planner
+        .add_static(6, task("lunch", range((12, 30), (13, 30))))
+        .unwrap();
+    planner
+        .add_static_allow_overlap(4, task("lunch", range((12, 30), (13, 30))))
+        .unwrap();
+}
+
+#[test]
+fn day_past_midnight_is_free_until_bed_time() {
+    let mut planner = planner_with(Config::new(time(18, 0), time(2, 0)).time_zone(Tz::UTC));
+    planner
+        .add_static(0, task("shift", range((23, 0), (1, 0))))
+        .unwrap();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(monday())),
+        [range((18, 0), (23, 0)), range((1, 0), (2, 0))]
+    );
+    assert!(planner
+        .add_static(0, task("late", range((2, 0), (3, 0))))
+        .is_err());
+}
+
+#[test]
+fn missing_hour_of_daylight_saving_time_isnt_free() {
+    let planner = planner_with(Config::new(time(20, 0), time(4, 0)).time_zone(Tz::Europe__Prague));
+    // clocks go from 02:00 to 03:00 in the night after
+    let date = chrono::NaiveDate::from_ymd_opt(2023, 3, 25).unwrap();
+
+    assert_eq!(
+        free(&planner.get_freetime_for(date)),
+        [range((20, 0), (2, 0)), range((3, 0), (4, 0))]
+    );
+}
+
+#[test]
+fn repeated_hour_of_daylight_saving_time_is_free_twice() {
+    let planner = planner_with(Config::new(time(20, 0), time(4, 0)).time_zone(Tz::Europe__Prague));
+    // clocks go from 03:00 back to 02:00 in the night after
+    let date = chrono::NaiveDate::from_ymd_opt(2023, 10, 28).unwrap();
+
+    let free = free(&planner.get_freetime_for(date));
+    assert_eq!(free, [range((20, 0), (3, 0)), range((2, 0), (4, 0))]);
+    assert_eq!(
+        free.iter()
+            .map(TimeRange::to_duration)
+            .fold(Duration::zero(), |total, free| total + free),
+        Duration::hours(9)
+    );
+}
+
+#[test]
+fn weekends_and_dates_have_their_own_hours() {
+    let wednesday = monday() + Duration::days(2);
+    let saturday = monday() + Duration::days(5);
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .weekends(time(9, 0), time(1, 0))
+        .date(wednesday, time(10, 0), time(20, 0));
+    let mut planner = planner_with(config.clone());
+    planner
+        .add_static(5, task("party", range((23, 0), (0, 30))))
+        .unwrap();
+    assert!(planner
+        .add_static(0, task("party", range((23, 0), (0, 30))))
+        .is_err());
+
+    assert_eq!(
+        free(&planner.get_freetime_for(saturday)),
+        [range((9, 0), (23, 0)), range((0, 30), (1, 0))]
+    );
+    assert_eq!(
+        free(&planner.get_freetime_for(wednesday)),
+        [range((10, 0), (20, 0))]
+    );
+    assert_eq!(
+        free(
+            &planner
+                .current_day()
+                .get_freetime(&config.for_weekday(Weekday::Sat))
+        ),
+        [range((9, 0), (1, 0))]
+    );
+}
+
+fn minutes(minutes: u32) -> chrono::NaiveTime {
+    time(minutes / 60 % 24, minutes % 60)
+}
+
+proptest! {
+    #[test]
+    fn free_time_is_the_day_without_its_tasks(
+        wake in 0..1440u32,
+        bed in 0..1440u32,
+        buffer in 0..60i64,
+        tasks in prop::collection::vec((0..1440u32, 1..240u32), 0..6),
+    ) {
+        let mut planner = planner();
+        let tasks = tasks
+            .into_iter()
+            .map(|(start, length)| TimeRange::new(minutes(start), minutes(start + length)))
+            .collect::<Vec<_>>();
+        for time in &tasks {
+            // a day starting with the task, so tasks can be outside of the day checked below
+            let config = Config::new(time.get_start(), time.get_start());
+            planner
+                .current_day_mut()
+                .add_static_allow_overlap(task("task", *time), &config)
+                .unwrap();
+        }
+
+        let config = Config::new(minutes(wake), minutes(bed))
+            .time_zone(Tz::UTC)
+            .buffer(Duration::minutes(buffer));
+        let day = config.get_day();
+        let schedule = planner.current_day().get_freetime(&config);
+
+        let statics = schedule
+            .iter()
+            .filter(|entry| matches!(entry, Schedule::Static(_)))
+            .count();
+        prop_assert_eq!(statics, tasks.iter().filter(|time| time.overlap(&day)).count());
+        for minute in 0..day.to_duration().num_minutes() as u32 {
+            let minute = TimeRange::new(minutes(wake + minute), minutes(wake + minute + 1));
+            let busy = tasks.iter().any(|time| time.overlap(&minute));
+            let covered = schedule
+                .iter()
+                .filter(|entry| !matches!(entry, Schedule::Static(_)))
+                .filter(|entry| entry.get_time().overlap(&minute))
+                .count();
+            prop_assert_eq!(covered, usize::from(!busy), "at {}", minute);
+        }
+    }
+}
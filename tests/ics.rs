@@ -0,0 +1,528 @@
+mod common;
+
+use chrono::Duration;
+use chrono_tz::Tz;
+use common::{monday, planner, planner_with, range, time};
+use lunite::{
+    Config, DynamicTask, LuniteError, ParseKind, PartOfDay, Recurrence, RecurringTask, Rule,
+    StaticTask, Task,
+};
+
+fn task(name: &str) -> Task {
+    Task::new(name.to_string(), String::new())
+}
+
+/// The unfolded lines of `ics`.
+fn unfold(ics: &str) -> Vec<String> {
+    ics.replace("\r\n ", "")
+        .split("\r\n")
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn export_is_in_the_planner_time_zone() {
+    let mut planner =
+        planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::Europe__Prague));
+    planner
+        .add_static(0, StaticTask::new(task("gym"), range((9, 0), (10, 0))))
+        .unwrap();
+
+    let lines = unfold(&planner.to_ics([monday()]).unwrap());
+    let line = |line: &str| lines.iter().any(|other| other == line);
+    assert!(line("DTSTART;TZID=Europe/Prague:20230501T090000"));
+    assert!(line("DTEND;TZID=Europe/Prague:20230501T100000"));
+    assert!(line("TZID:Europe/Prague"));
+    // the offset changes by yearly rules, from a year before on
+    assert!(line("DTSTART:20221030T030000"));
+    assert!(line("RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU"));
+    assert!(line("DTSTART:20230326T020000"));
+    assert!(line("RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU"));
+    assert!(line("TZOFFSETFROM:+0200"));
+    assert!(line("TZOFFSETTO:+0100"));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| *line == "BEGIN:STANDARD")
+            .count(),
+        1
+    );
+
+    // a zone changing its offset for good gets the change itself
+    let planner =
+        planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::Europe__Volgograd));
+    let lines = unfold(&planner.to_ics([monday() - Duration::weeks(150)]).unwrap());
+    let line = |line: &str| lines.iter().any(|other| other == line);
+    assert!(line("DTSTART:20201227T020000"));
+    assert!(line("TZOFFSETFROM:+0400"));
+    assert!(line("TZOFFSETTO:+0300"));
+    assert!(!lines.iter().any(|line| line.starts_with("RRULE:")));
+
+    let mut planner = planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::UTC));
+    planner
+        .add_static(0, StaticTask::new(task("gym"), range((9, 0), (10, 0))))
+        .unwrap();
+    let lines = unfold(&planner.to_ics([monday()]).unwrap());
+    assert!(lines.iter().any(|line| line == "DTSTART:20230501T090000Z"));
+    assert!(!lines.iter().any(|line| line == "BEGIN:VTIMEZONE"));
+}
+
+#[test]
+fn skipped_dates_of_static_tasks_are_left_out() {
+    let mut planner = planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::UTC));
+    planner
+        .add_static(0, StaticTask::new(task("gym"), range((9, 0), (10, 0))))
+        .unwrap();
+    planner
+        .skip_static(monday() + Duration::weeks(1), 0)
+        .unwrap();
+
+    let lines = unfold(&planner.to_ics([monday()]).unwrap());
+    assert!(lines
+        .iter()
+        .any(|line| line == "RRULE:FREQ=WEEKLY;BYDAY=MO"));
+    assert!(lines.iter().any(|line| line == "EXDATE:20230508T090000Z"));
+}
+
+#[test]
+fn tasks_are_exported_as_events() {
+    let mut planner = planner();
+    let gym = Task::new(
+        String::from("gym, legs; heavy"),
+        String::from("squats\\deadlifts\nlunges"),
+    )
+    .location(String::from("Gym"));
+    let gym_uuid = *gym.get_uuid();
+    planner
+        .add_static(0, StaticTask::new(gym, range((9, 0), (10, 0))))
+        .unwrap();
+
+    let ics = planner.to_ics([monday()]).unwrap();
+    let lines = unfold(&ics);
+    assert_eq!(lines.first().map(String::as_str), Some("BEGIN:VCALENDAR"));
+    assert_eq!(
+        lines.iter().filter(|line| *line == "BEGIN:VEVENT").count(),
+        1
+    );
+    for line in [
+        format!("UID:{gym_uuid}"),
+        String::from("DTSTAMP:20230501T080000Z"),
+        String::from("DTSTART:20230501T090000Z"),
+        String::from("DTEND:20230501T100000Z"),
+        String::from("RRULE:FREQ=WEEKLY;BYDAY=MO"),
+        String::from("SUMMARY:gym\\, legs\\; heavy"),
+        String::from("DESCRIPTION:squats\\\\deadlifts\\nlunges"),
+        String::from("LOCATION:Gym"),
+    ] {
+        assert!(lines.contains(&line), "{line} is missing from {ics}");
+    }
+    assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+}
+
+#[test]
+fn long_lines_are_folded_at_75_octets() {
+    let mut planner = planner();
+    let name = "čtení ".repeat(20);
+    planner
+        .add_static(0, StaticTask::new(task(&name), range((9, 0), (10, 0))))
+        .unwrap();
+
+    let ics = planner.to_ics([monday()]).unwrap();
+    let lines = ics.split("\r\n").collect::<Vec<_>>();
+    assert!(lines.iter().all(|line| line.len() <= 75));
+    assert!(lines.iter().any(|line| line.starts_with(' ')));
+    assert!(unfold(&ics).contains(&format!("SUMMARY:{name}")));
+}
+
+#[test]
+fn recurring_tasks_are_exported_with_rules_and_exceptions() {
+    let mut planner = planner();
+    let recurrence = Recurrence::new(Rule::Days(2), monday())
+        .until(monday() + Duration::days(6))
+        .except(monday() + Duration::days(2));
+    planner
+        .add_recurring(RecurringTask::new(
+            StaticTask::new(task("sync"), range((10, 0), (11, 0))),
+            recurrence,
+        ))
+        .unwrap();
+    let recurrence = Recurrence::new(Rule::MonthlyOn(-1, chrono::Weekday::Fri), monday());
+    planner
+        .add_recurring(RecurringTask::new(
+            StaticTask::new(task("review"), range((14, 0), (15, 0))),
+            recurrence,
+        ))
+        .unwrap();
+
+    let dates = (0..28)
+        .map(|day| monday() + Duration::days(day))
+        .collect::<Vec<_>>();
+    let lines = unfold(&planner.to_ics(dates).unwrap());
+    // each recurring task is a single event, starting on its first date
+    assert_eq!(
+        lines.iter().filter(|line| *line == "BEGIN:VEVENT").count(),
+        2
+    );
+    for line in [
+        "DTSTART:20230501T100000Z",
+        "RRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20230507T235959Z",
+        "EXDATE:20230503T100000Z",
+        "DTSTART:20230526T140000Z",
+        "RRULE:FREQ=MONTHLY;BYDAY=-1FR",
+    ] {
+        assert!(lines.iter().any(|other| other == line), "{line} is missing");
+    }
+}
+
+#[test]
+fn split_parts_get_numbered_uids() {
+    let mut planner = planner();
+    let study = DynamicTask::new_flexible(
+        task("study"),
+        monday(),
+        Duration::hours(3),
+        PartOfDay::Morning,
+        true,
+    )
+    .chunks(None, Some(Duration::hours(1)), None)
+    .unwrap();
+    let uuid = *study.get_task().get_uuid();
+    planner.add_dynamic(study).unwrap();
+
+    let lines = unfold(&planner.to_ics([monday()]).unwrap());
+    for nth in 1..=3 {
+        assert!(lines.contains(&format!("UID:{uuid}-{nth}")));
+        assert!(lines.contains(&format!("SUMMARY:study (part {nth}/3)")));
+    }
+    assert!(!lines.contains(&format!("UID:{uuid}")));
+}
+
+#[test]
+fn weekly_events_become_static_tasks() {
+    let mut planner = planner();
+    let ics = [
+        "BEGIN:VCALENDAR",
+        "BEGIN:VEVENT",
+        "UID:67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "SUMMARY:gym\\, legs",
+        "DESCRIPTION:squats\\nlunges",
+        "DTSTART:20230501T090000Z",
+        "DTEND:20230501T100000Z",
+        "RRULE:FREQ=WEEKLY;BYDAY=MO,WE",
+        "BEGIN:VALARM",
+        "DESCRIPTION:ignored",
+        "END:VALARM",
+        "END:VEVENT",
+        "END:VCALENDAR",
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Morning).unwrap();
+    assert_eq!((report.statics, report.dynamics), (2, 0));
+    assert!(report.skipped.is_empty());
+    for day in [0, 2] {
+        let gym = &planner.nth_day(day).unwrap().get_static_tasks()[0];
+        assert_eq!(gym.get_task().get_name(), "gym, legs");
+        assert_eq!(gym.get_task().get_description(), "squats\nlunges");
+        assert_eq!(*gym.get_time(), range((9, 0), (10, 0)));
+    }
+    // only the first day keeps the uid
+    assert_eq!(
+        planner.nth_day(0).unwrap().get_static_tasks()[0]
+            .get_task()
+            .get_uuid()
+            .to_string(),
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    );
+    assert!(planner.nth_day(1).unwrap().get_static_tasks().is_empty());
+}
+
+#[test]
+fn single_events_and_to_dos_become_dynamic_tasks() {
+    let mut planner = planner();
+    let ics = [
+        "BEGIN:VCALENDAR",
+        "BEGIN:VEVENT",
+        "SUMMARY:dentist",
+        "DTSTART:20230502T140000",
+        "DURATION:PT45M",
+        "END:VEVENT",
+        "BEGIN:VTODO",
+        "SUMMARY:taxes",
+        "DTSTART;VALUE=DATE:20230503",
+        "DURATION:PT1H30M",
+        "PRIORITY:1",
+        "END:VTODO",
+        "END:VCALENDAR",
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Evening).unwrap();
+    assert_eq!((report.statics, report.dynamics), (0, 2));
+    let named = |name: &str| {
+        planner
+            .get_dynamic_tasks()
+            .iter()
+            .find(|task| task.get_task().get_name() == name)
+            .unwrap()
+    };
+    let DynamicTask::Fixed { task, date, .. } = named("dentist") else {
+        panic!("expected a fixed task");
+    };
+    assert_eq!(*date, monday() + Duration::days(1));
+    assert_eq!(*task.get_time(), range((14, 0), (14, 45)));
+    let DynamicTask::Flexible {
+        date,
+        length,
+        around,
+        priority,
+        ..
+    } = named("taxes")
+    else {
+        panic!("expected a flexible task");
+    };
+    assert_eq!(*date, monday() + Duration::days(2));
+    assert_eq!(*length, Duration::minutes(90));
+    assert_eq!(*around, PartOfDay::Evening);
+    assert_eq!(*priority, 9);
+    assert_eq!(named("taxes").get_deadline(), None);
+}
+
+#[test]
+fn unsupported_components_are_skipped() {
+    let mut planner = planner();
+    let ics = [
+        "BEGIN:VCALENDAR",
+        "BEGIN:VEVENT",
+        "SUMMARY:standup",
+        "DTSTART:20230501T090000Z",
+        "DTEND:20230501T091500Z",
+        "RRULE:FREQ=WEEKLY;COUNT=10",
+        "END:VEVENT",
+        "BEGIN:VEVENT",
+        "SUMMARY:holiday",
+        "DTSTART;VALUE=DATE:20230501",
+        "END:VEVENT",
+        "BEGIN:VEVENT",
+        "SUMMARY:conference",
+        "DTSTART:20230501T090000Z",
+        "DURATION:P2D",
+        "END:VEVENT",
+        "BEGIN:VTODO",
+        "SUMMARY:call",
+        "DTSTART:20230501T090000Z",
+        "DURATION:PT15M",
+        "END:VTODO",
+        "BEGIN:VTODO",
+        "SUMMARY:read",
+        "DTSTART;VALUE=DATE:20230501",
+        "DURATION:soon",
+        "END:VTODO",
+        "BEGIN:VEVENT",
+        "SUMMARY:lunch",
+        "DTSTART:20230501T120000Z",
+        "DTEND:20230501T130000Z",
+        "END:VEVENT",
+        "END:VCALENDAR",
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Morning).unwrap();
+    assert_eq!((report.statics, report.dynamics), (0, 1));
+    let reasons = report
+        .skipped
+        .iter()
+        .map(|(summary, e)| (summary.as_str(), e))
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        reasons[..],
+        [
+            ("standup", LuniteError::UnsupportedRule(_)),
+            ("holiday", LuniteError::AllDayEvent),
+            ("conference", LuniteError::EventTooLong(_)),
+            ("call", LuniteError::TimedTodo),
+            ("read", LuniteError::InvalidProperty { .. }),
+        ]
+    ));
+
+    assert!(matches!(
+        planner.import_ics("BEGIN:VEVENT\r\nEND:VEVENT", PartOfDay::Morning),
+        Err(LuniteError::Parse {
+            kind: ParseKind::Calendar,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn imported_times_are_converted_from_their_time_zone() {
+    let mut planner =
+        planner_with(Config::new(time(6, 0), time(22, 0)).time_zone(Tz::Europe__Prague));
+    let ics = [
+        "BEGIN:VCALENDAR",
+        "BEGIN:VEVENT",
+        "SUMMARY:call",
+        "DTSTART;TZID=America/New_York:20230502T090000",
+        "DTEND;TZID=America/New_York:20230502T100000",
+        "END:VEVENT",
+        "BEGIN:VTODO",
+        "SUMMARY:taxes",
+        "DUE;VALUE=DATE:20230505",
+        "DURATION:PT1H",
+        "END:VTODO",
+        "END:VCALENDAR",
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Morning).unwrap();
+    assert_eq!(report.dynamics, 2);
+    let named = |name: &str| {
+        planner
+            .get_dynamic_tasks()
+            .iter()
+            .find(|task| task.get_task().get_name() == name)
+            .unwrap()
+    };
+    let DynamicTask::Fixed {
+        task: call, date, ..
+    } = named("call")
+    else {
+        panic!("expected a fixed task");
+    };
+    assert_eq!(*date, monday() + Duration::days(1));
+    assert_eq!(*call.get_time(), range((15, 0), (16, 0)));
+    assert_eq!(*named("taxes").get_date(), monday());
+    assert_eq!(
+        named("taxes").get_deadline(),
+        Some(&(monday() + Duration::days(4)))
+    );
+}
+
+#[test]
+fn skipped_components_say_why() {
+    let mut planner = planner();
+    let gym = task("gym");
+    let gym_uuid = *gym.get_uuid();
+    planner
+        .add_static(0, StaticTask::new(gym, range((9, 0), (10, 0))))
+        .unwrap();
+    let ics = [
+        "BEGIN:VCALENDAR",
+        "BEGIN:VEVENT",
+        "SUMMARY:standup",
+        "DTSTART:20230501T093000Z",
+        "DTEND:20230501T094500Z",
+        "RRULE:FREQ=WEEKLY",
+        "END:VEVENT",
+        "BEGIN:VEVENT",
+        "SUMMARY:payday",
+        "DTSTART:20230501T120000Z",
+        "DTEND:20230501T130000Z",
+        "RRULE:FREQ=MONTHLY",
+        "END:VEVENT",
+        "BEGIN:VEVENT",
+        "SUMMARY:someday",
+        "END:VEVENT",
+        "END:VCALENDAR",
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Morning).unwrap();
+    assert_eq!(report.statics + report.dynamics, 0);
+    let reason = |summary: &str| {
+        &report
+            .skipped
+            .iter()
+            .find(|(other, _)| other == summary)
+            .unwrap()
+            .1
+    };
+    assert!(matches!(
+        reason("standup"),
+        LuniteError::Conflict { task_uuid, .. } if *task_uuid == gym_uuid
+    ));
+    assert!(matches!(
+        reason("payday"),
+        LuniteError::UnsupportedRule(rule) if rule == "FREQ=MONTHLY"
+    ));
+    assert!(matches!(
+        reason("someday"),
+        LuniteError::MissingProperty("DTSTART")
+    ));
+}
+
+#[test]
+fn events_are_imported_whole_or_not_at_all() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .weekends(time(5, 0), time(22, 0));
+    let mut planner = planner_with(config);
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((9, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_recurring(RecurringTask::new(
+            StaticTask::new(task("sync"), range((15, 0), (16, 0))),
+            Recurrence::new(Rule::Weeks(2), monday()),
+        ))
+        .unwrap();
+    let event = |summary: &str, start: &str, end: &str, rrule: Option<&str>| {
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("SUMMARY:{summary}"),
+            format!("DTSTART:{start}"),
+            format!("DTEND:{end}"),
+        ];
+        lines.extend(rrule.map(|rrule| format!("RRULE:{rrule}")));
+        lines.push("END:VEVENT".to_string());
+        lines.join("\r\n")
+    };
+    let weekly = Some("FREQ=WEEKLY;BYDAY=TU,SA");
+    let ics = [
+        "BEGIN:VCALENDAR".to_string(),
+        event("call", "20230501T100000Z", "20230501T103000Z", None),
+        event("review", "20230515T153000Z", "20230515T163000Z", None),
+        event(
+            "standup",
+            "20230501T150000Z",
+            "20230501T153000Z",
+            Some("FREQ=WEEKLY;BYDAY=WE,MO"),
+        ),
+        event("swim", "20230502T050000Z", "20230502T060000Z", weekly),
+        event("run", "20230502T130000Z", "20230502T140000Z", weekly),
+        "END:VCALENDAR".to_string(),
+    ]
+    .join("\r\n");
+
+    let report = planner.import_ics(&ics, PartOfDay::Morning).unwrap();
+    assert_eq!((report.statics, report.dynamics), (2, 0));
+    let reason = |summary: &str| {
+        &report
+            .skipped
+            .iter()
+            .find(|(other, _)| other == summary)
+            .unwrap()
+            .1
+    };
+    // single events clash with the static and recurring tasks of their date
+    assert!(matches!(
+        reason("call"),
+        LuniteError::Conflict { name, .. } if name == "work"
+    ));
+    assert!(matches!(
+        reason("review"),
+        LuniteError::Conflict { name, .. } if name == "sync"
+    ));
+    // a weekday clashing or outside of the waking hours leaves out every other one too
+    assert!(matches!(
+        reason("standup"),
+        LuniteError::Conflict { name, .. } if name == "sync"
+    ));
+    assert!(matches!(reason("swim"), LuniteError::OutsideOfDay { .. }));
+    assert!(planner.nth_day(2).unwrap().get_static_tasks().is_empty());
+    for day in [1, 5] {
+        let statics = planner.nth_day(day).unwrap().get_static_tasks();
+        assert_eq!(statics.len(), 1);
+        assert_eq!(statics[0].get_task().get_name(), "run");
+    }
+}
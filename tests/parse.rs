@@ -0,0 +1,155 @@
+mod common;
+
+use chrono::{Duration, NaiveDate, Weekday};
+use common::{monday, range, time};
+use lunite::{
+    parse::{parse_date, parse_duration, parse_range, parse_time, parse_weekday},
+    LuniteError, ParseKind, PartOfDay, Rollover, Rule, Strategy,
+};
+
+/// Whether `result` failed to parse as a `kind`.
+fn fails_as<T>(result: Result<T, LuniteError>, kind: ParseKind) -> bool {
+    matches!(result, Err(LuniteError::Parse { kind: other, .. }) if other == kind)
+}
+
+#[test]
+fn times_parse_in_24_and_12_hour_formats() {
+    assert_eq!(parse_time("14:00").unwrap(), time(14, 0));
+    assert_eq!(parse_time(" 9:30 ").unwrap(), time(9, 30));
+    assert_eq!(parse_time("14").unwrap(), time(14, 0));
+    assert_eq!(parse_time("2pm").unwrap(), time(14, 0));
+    assert_eq!(parse_time("9:30AM").unwrap(), time(9, 30));
+    assert_eq!(parse_time("12am").unwrap(), time(0, 0));
+    assert_eq!(parse_time("12pm").unwrap(), time(12, 0));
+
+    for invalid in ["", "24:00", "9:60", "13pm", "0am", "noon", "9:3x"] {
+        assert!(fails_as(parse_time(invalid), ParseKind::Time), "{invalid}");
+    }
+}
+
+#[test]
+fn ranges_parse_with_their_ends() {
+    assert_eq!(parse_range("14:00-16:00").unwrap(), range((14, 0), (16, 0)));
+    assert_eq!(parse_range("9am-11:30am").unwrap(), range((9, 0), (11, 30)));
+    // past midnight
+    assert_eq!(parse_range("22:00-02:00").unwrap(), range((22, 0), (2, 0)));
+
+    assert!(fails_as(parse_range("14:00"), ParseKind::Range));
+    assert!(fails_as(parse_range("14:00-14:00"), ParseKind::EmptyRange));
+    assert!(fails_as(parse_range("14:00-late"), ParseKind::Time));
+}
+
+#[test]
+fn durations_parse_in_hours_and_minutes() {
+    assert_eq!(parse_duration("90m").unwrap(), Duration::minutes(90));
+    assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+    assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+    assert_eq!(parse_duration("1.5h").unwrap(), Duration::minutes(90));
+    assert_eq!(parse_duration("1h 15m").unwrap(), Duration::minutes(75));
+    // a bare number means minutes
+    assert_eq!(parse_duration("90").unwrap(), Duration::minutes(90));
+
+    for invalid in [
+        "",
+        "0m",
+        "h",
+        "1.5.5h",
+        "2 days",
+        "-1h",
+        // too long for a duration
+        "999999999999999999",
+        "99999999999999h",
+        "2000000000000h 2000000000000h",
+    ] {
+        assert!(
+            fails_as(parse_duration(invalid), ParseKind::Duration),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn dates_parse_relative_to_today() {
+    let today = monday() + Duration::days(2);
+    assert_eq!(parse_date("today", today).unwrap(), today);
+    assert_eq!(
+        parse_date("Tomorrow", today).unwrap(),
+        today + Duration::days(1)
+    );
+    assert_eq!(
+        parse_date("yesterday", today).unwrap(),
+        today - Duration::days(1)
+    );
+    assert_eq!(parse_date("+3", today).unwrap(), today + Duration::days(3));
+    assert_eq!(
+        parse_date("2023-04-26", today).unwrap(),
+        NaiveDate::from_ymd_opt(2023, 4, 26).unwrap()
+    );
+    // weekdays are their next occurrence, today included
+    assert_eq!(parse_date("wed", today).unwrap(), today);
+    assert_eq!(
+        parse_date("friday", today).unwrap(),
+        today + Duration::days(2)
+    );
+    assert_eq!(parse_date("mon", today).unwrap(), today + Duration::days(5));
+
+    for invalid in ["", "+", "+x", "-3", "2023-13-01", "someday"] {
+        assert!(
+            fails_as(parse_date(invalid, today), ParseKind::Date),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn weekdays_parse_by_short_and_full_names() {
+    assert_eq!(parse_weekday("mon").unwrap(), Weekday::Mon);
+    assert_eq!(parse_weekday("Sunday").unwrap(), Weekday::Sun);
+    assert!(fails_as(parse_weekday("someday"), ParseKind::Weekday));
+}
+
+#[test]
+fn named_values_parse_from_strings() {
+    assert_eq!("Evening".parse::<PartOfDay>().unwrap(), PartOfDay::Evening);
+    assert_eq!(
+        "9:00-10:00".parse::<PartOfDay>().unwrap(),
+        PartOfDay::Fixed(range((9, 0), (10, 0)))
+    );
+    assert!(fails_as("brunch".parse::<PartOfDay>(), ParseKind::Range));
+
+    assert_eq!("first_fit".parse::<Strategy>().unwrap(), Strategy::FirstFit);
+    assert_eq!(
+        "edf".parse::<Strategy>().unwrap(),
+        Strategy::EarliestDeadlineFirst
+    );
+    assert!(fails_as("random".parse::<Strategy>(), ParseKind::Strategy));
+
+    assert_eq!("next".parse::<Rollover>().unwrap(), Rollover::NextFreeDay);
+    assert_eq!("keep".parse::<Rollover>().unwrap(), Rollover::Keep);
+    assert!(fails_as("later".parse::<Rollover>(), ParseKind::Rollover));
+}
+
+#[test]
+fn rules_parse_from_strings() {
+    assert_eq!("daily".parse::<Rule>().unwrap(), Rule::Days(1));
+    assert_eq!("weekdays".parse::<Rule>().unwrap(), Rule::Weekdays);
+    assert_eq!("biweekly".parse::<Rule>().unwrap(), Rule::Weeks(2));
+    assert_eq!("every 3 days".parse::<Rule>().unwrap(), Rule::Days(3));
+    assert_eq!("every-2-weeks".parse::<Rule>().unwrap(), Rule::Weeks(2));
+    assert_eq!(
+        "2nd-tue".parse::<Rule>().unwrap(),
+        Rule::MonthlyOn(2, Weekday::Tue)
+    );
+    assert_eq!(
+        "last fri".parse::<Rule>().unwrap(),
+        Rule::MonthlyOn(-1, Weekday::Fri)
+    );
+
+    for invalid in ["", "monthly", "every-x-days", "every-2-months", "6th-mon"] {
+        assert!(
+            fails_as(invalid.parse::<Rule>(), ParseKind::Rule),
+            "{invalid}"
+        );
+    }
+    assert!(fails_as("2nd-someday".parse::<Rule>(), ParseKind::Weekday));
+}
@@ -0,0 +1,568 @@
+mod common;
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use common::{free, monday, planner, planner_with, range, time};
+use lunite::{
+    Break, Config, DynamicTask, FixedClock, LuniteError, PartOfDay, Rollover, Schedule,
+    ScheduleDiagnostic, StaticTask, Strategy, Task, TimeRange,
+};
+
+fn task(name: &str) -> Task {
+    Task::new(name.to_string(), String::new())
+}
+
+fn flexible(name: &str, minutes: i64, around: PartOfDay) -> DynamicTask {
+    DynamicTask::new_flexible(
+        task(name),
+        monday(),
+        Duration::minutes(minutes),
+        around,
+        false,
+    )
+}
+
+/// Time every dynamic task or part called `name` got.
+fn placed(schedule: &[Schedule], name: &str) -> Vec<TimeRange> {
+    schedule
+        .iter()
+        .filter_map(|entry| match entry {
+            Schedule::DynamicTask(task, time) if task.get_task().get_name() == name => Some(*time),
+            Schedule::DynamicPart { of, time, .. } if of.get_task().get_name() == name => {
+                Some(*time)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn fixed_task_takes_its_time() {
+    let mut planner = planner();
+    let time = range((10, 0), (11, 0));
+    planner
+        .add_dynamic(DynamicTask::new_fixed(
+            StaticTask::new(task("call"), time),
+            monday(),
+        ))
+        .unwrap();
+
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "call"), [time]);
+    assert_eq!(
+        free(&schedule),
+        [range((6, 0), (10, 0)), range((11, 0), (22, 0))]
+    );
+}
+
+#[test]
+fn flexible_task_goes_into_its_part_of_day() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((12, 0), (17, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(flexible("walk", 60, PartOfDay::Evening))
+        .unwrap();
+
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "walk"), [range((18, 0), (19, 0))]);
+}
+
+#[test]
+fn flexible_task_without_room_is_reported() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((6, 0), (21, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(flexible("study", 120, PartOfDay::Morning))
+        .unwrap();
+
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(placed(&schedule, "study").is_empty());
+    assert!(matches!(
+        diagnostics[..],
+        [ScheduleDiagnostic::Unplaced(LuniteError::NotEnoughFreeTime { needed, available, .. })]
+            if needed == Duration::hours(2) && available == Duration::hours(1)
+    ));
+}
+
+#[test]
+fn splittable_task_fills_gaps_between_statics() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((7, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(DynamicTask::new_flexible(
+            task("study"),
+            monday(),
+            Duration::minutes(90),
+            PartOfDay::Morning,
+            true,
+        ))
+        .unwrap();
+
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    let parts = placed(&schedule, "study");
+    assert_eq!(parts, [range((6, 0), (7, 0)), range((12, 0), (12, 30))]);
+    assert!(matches!(
+        diagnostics[..],
+        [ScheduleDiagnostic::OutsidePartOfDay { .. }]
+    ));
+}
+
+#[test]
+fn split_parts_are_numbered_in_the_order_they_happen() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((8, 0), (22, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(DynamicTask::new_flexible(
+            task("study"),
+            monday(),
+            Duration::minutes(90),
+            PartOfDay::Fixed(range((7, 0), (8, 0))),
+            true,
+        ))
+        .unwrap();
+
+    // the part in its part of day is placed first but happens last
+    let (schedule, _) = planner.schedule_for(monday());
+    let parts = schedule
+        .iter()
+        .filter_map(|entry| match entry {
+            Schedule::DynamicPart { nth, time, .. } => Some((*nth, *time)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parts,
+        [(1, range((6, 0), (6, 30))), (2, range((7, 0), (8, 0)))]
+    );
+}
+
+#[test]
+fn split_parts_keep_to_chunk_limits() {
+    let planner_with_gap = |gap| {
+        let config = Config::new(time(6, 0), time(22, 0))
+            .time_zone(Tz::UTC)
+            .part_gap(Duration::minutes(gap));
+        let mut planner = planner_with(config);
+        planner
+            .add_static(0, StaticTask::new(task("work"), range((6, 10), (9, 0))))
+            .unwrap();
+        planner
+            .add_static(0, StaticTask::new(task("lunch"), range((12, 0), (22, 0))))
+            .unwrap();
+        planner
+    };
+    let study = |can_split| {
+        DynamicTask::new_flexible(
+            task("study"),
+            monday(),
+            Duration::minutes(150),
+            PartOfDay::Morning,
+            can_split,
+        )
+    };
+    let minutes = |minutes| Some(Duration::minutes(minutes));
+    for (min, max, max_parts) in [
+        (minutes(60), minutes(30), None),
+        // two parts are too short and three too long
+        (minutes(60), minutes(70), None),
+        (None, minutes(60), Some(2)),
+        (minutes(0), None, None),
+    ] {
+        assert!(
+            matches!(
+                study(true).chunks(min, max, max_parts),
+                Err(LuniteError::InvalidChunks { .. })
+            ),
+            "{min:?} {max:?} {max_parts:?}"
+        );
+    }
+    assert!(matches!(
+        study(false).chunks(None, minutes(60), None),
+        Err(LuniteError::ExpectedSplittable)
+    ));
+
+    let limited = || {
+        study(true)
+            .chunks(minutes(30), minutes(60), Some(3))
+            .unwrap()
+    };
+    assert!(matches!(
+        limited().fixed_split(&Duration::minutes(10), 1),
+        Err(LuniteError::InvalidChunk { .. })
+    ));
+    assert!(limited().fixed_split(&Duration::minutes(40), 4).is_err());
+    let mut planner = planner_with_gap(0);
+    planner.add_dynamic(limited()).unwrap();
+
+    // the 10 minutes before work are too short and the last part can't be left under 30
+    // minutes
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(
+        placed(&schedule, "study"),
+        [
+            range((9, 0), (10, 0)),
+            range((10, 0), (11, 0)),
+            range((11, 0), (11, 30))
+        ]
+    );
+
+    // unless the config keeps the parts apart
+    let mut planner = planner_with_gap(15);
+    planner.add_dynamic(limited()).unwrap();
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(
+        placed(&schedule, "study"),
+        [
+            range((9, 0), (10, 0)),
+            range((10, 15), (11, 15)),
+            range((11, 30), (12, 0))
+        ]
+    );
+}
+
+#[test]
+fn flexible_tasks_keep_their_buffers() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .buffer(Duration::minutes(10));
+    let mut planner = planner_with(config);
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((7, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(flexible("walk", 30, PartOfDay::Morning))
+        .unwrap();
+    planner
+        .add_dynamic(flexible("call", 30, PartOfDay::Morning))
+        .unwrap();
+
+    // 06:00 to 06:50 only holds one of them with a buffer on either side
+    let (schedule, _) = planner.schedule_for(monday());
+    assert_eq!(placed(&schedule, "walk"), [range((6, 10), (6, 40))]);
+    assert_eq!(placed(&schedule, "call"), [range((12, 20), (12, 50))]);
+    assert!(matches!(schedule[0], Schedule::Buffer(_)));
+}
+
+#[test]
+fn higher_priority_task_preempts_lower_one() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((8, 0), (22, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(DynamicTask::new_fixed(
+            StaticTask::new(task("chat"), range((6, 0), (8, 0))),
+            monday(),
+        ))
+        .unwrap();
+    planner
+        .add_dynamic(flexible("exam", 120, PartOfDay::Morning).priority(5))
+        .unwrap();
+
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert_eq!(placed(&schedule, "exam"), [range((6, 0), (8, 0))]);
+    assert!(placed(&schedule, "chat").is_empty());
+    assert!(matches!(
+        diagnostics[..],
+        [ScheduleDiagnostic::Preempted { .. }]
+    ));
+}
+
+#[test]
+fn strategies_pick_different_ranges() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((7, 0), (20, 0))))
+        .unwrap();
+    planner
+        .add_dynamic(flexible(
+            "read",
+            60,
+            PartOfDay::Fixed(range((6, 0), (22, 0))),
+        ))
+        .unwrap();
+
+    let (first, _) = planner.schedule_with_scheduler_for(monday(), &Strategy::FirstFit);
+    let (spread, _) = planner.schedule_with_scheduler_for(monday(), &Strategy::SpreadEvenly);
+    assert_eq!(placed(&first, "read"), [range((6, 0), (7, 0))]);
+    assert_eq!(placed(&spread, "read"), [range((20, 30), (21, 30))]);
+}
+
+#[test]
+fn optimal_moves_tasks_to_make_room() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((9, 0), (12, 0))))
+        .unwrap();
+    planner
+        .add_static(0, StaticTask::new(task("gym"), range((14, 0), (22, 0))))
+        .unwrap();
+    let day = PartOfDay::Fixed(range((6, 0), (22, 0)));
+    planner
+        .add_dynamic(
+            DynamicTask::new_flexible(task("study"), monday(), Duration::hours(2), day, true)
+                .priority(5),
+        )
+        .unwrap();
+    planner.add_dynamic(flexible("essay", 180, day)).unwrap();
+
+    let (first, _) = planner.schedule_with_scheduler_for(monday(), &Strategy::FirstFit);
+    assert_eq!(placed(&first, "study"), [range((6, 0), (8, 0))]);
+    assert!(placed(&first, "essay").is_empty());
+
+    let (optimal, diagnostics) = planner.schedule_with_scheduler_for(monday(), &Strategy::Optimal);
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&optimal, "essay"), [range((6, 0), (9, 0))]);
+    assert_eq!(placed(&optimal, "study"), [range((12, 0), (14, 0))]);
+}
+
+#[test]
+fn earliest_deadline_first_places_tasks_due_sooner_first() {
+    let mut planner = planner();
+    for (name, days) in [("essay", 2), ("report", 1)] {
+        planner
+            .add_dynamic(
+                flexible(name, 60, PartOfDay::Morning)
+                    .deadline(monday() + Duration::days(days))
+                    .unwrap(),
+            )
+            .unwrap();
+    }
+    planner
+        .add_dynamic(flexible("email", 60, PartOfDay::Morning))
+        .unwrap();
+
+    let (schedule, diagnostics) =
+        planner.schedule_with_scheduler_for(monday(), &Strategy::EarliestDeadlineFirst);
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "email"), [range((6, 0), (7, 0))]);
+    assert_eq!(placed(&schedule, "report"), [range((7, 0), (8, 0))]);
+    assert_eq!(placed(&schedule, "essay"), [range((8, 0), (9, 0))]);
+}
+
+#[test]
+fn dates_come_from_the_clock() {
+    let mut planner = planner();
+    assert_eq!(planner.today(), monday());
+    assert!(matches!(
+        planner.add_dynamic(DynamicTask::new_flexible(
+            task("late"),
+            monday() - Duration::days(1),
+            Duration::hours(1),
+            PartOfDay::Morning,
+            false,
+        )),
+        Err(LuniteError::DateInPast(_))
+    ));
+
+    let friday = NaiveDate::from_ymd_opt(2023, 5, 5).unwrap();
+    planner.set_clock(FixedClock(
+        Utc.from_utc_datetime(&friday.and_time(time(12, 0))),
+    ));
+    assert_eq!(planner.today(), friday);
+    assert_eq!(planner.get_freetime_nth(0).unwrap().len(), 1);
+    assert!(matches!(
+        planner.add_dynamic(flexible("old", 60, PartOfDay::Morning)),
+        Err(LuniteError::DateInPast(_))
+    ));
+}
+
+#[test]
+fn deadline_tasks_move_to_the_first_day_with_room() {
+    let mut planner = planner();
+    planner
+        .add_static(0, StaticTask::new(task("work"), range((6, 0), (21, 0))))
+        .unwrap();
+    let tuesday = monday() + Duration::days(1);
+    planner
+        .add_dynamic(
+            flexible("essay", 120, PartOfDay::Morning)
+                .deadline(monday() + Duration::days(2))
+                .unwrap(),
+        )
+        .unwrap();
+    planner
+        .add_dynamic(
+            flexible("report", 120, PartOfDay::Morning)
+                .deadline(monday())
+                .unwrap(),
+        )
+        .unwrap();
+
+    let (schedule, _) = planner.schedule_for(monday());
+    assert!(placed(&schedule, "essay").is_empty());
+    let (schedule, diagnostics) = planner.schedule_for(tuesday);
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "essay"), [range((6, 0), (8, 0))]);
+
+    let at_risk = planner.at_risk();
+    assert_eq!(at_risk.len(), 1);
+    assert_eq!(at_risk[0].get_task().get_name(), "report");
+
+    // a plan made once picks the same dates as every query picking them again
+    let plan = planner.deadline_plan();
+    let (schedule, _) = planner.schedule_planned(tuesday, &plan);
+    assert_eq!(placed(&schedule, "essay"), [range((6, 0), (8, 0))]);
+    let at_risk = planner.at_risk_in(&plan);
+    assert_eq!(at_risk.len(), 1);
+    assert_eq!(at_risk[0].get_task().get_name(), "report");
+    // and still is once tasks are done
+    let report = planner
+        .get_dynamic_tasks()
+        .iter()
+        .position(|task| task.get_task().get_name() == "report")
+        .unwrap();
+    planner.complete_dynamic(report).unwrap();
+    assert!(planner.at_risk_in(&plan).is_empty());
+    let (schedule, _) = planner.schedule_planned(tuesday, &plan);
+    assert_eq!(placed(&schedule, "essay"), [range((6, 0), (8, 0))]);
+    assert!(matches!(
+        flexible("late", 60, PartOfDay::Morning).deadline(monday() - Duration::days(1)),
+        Err(LuniteError::DeadlineBeforeStart { .. })
+    ));
+}
+
+#[test]
+fn unfinished_tasks_roll_over_by_policy() {
+    let wednesday = monday() + Duration::days(2);
+    let clock = FixedClock(Utc.from_utc_datetime(&wednesday.and_time(time(8, 0))));
+    let policies = [
+        Rollover::Keep,
+        Rollover::Drop,
+        Rollover::NextFreeDay,
+        Rollover::Overdue,
+    ];
+    let mut planners = policies.map(|rollover| {
+        let config = Config::new(time(6, 0), time(22, 0))
+            .time_zone(chrono_tz::Tz::UTC)
+            .rollover(rollover);
+        let mut planner = planner_with(config);
+        planner
+            .add_static(2, StaticTask::new(task("work"), range((6, 0), (21, 0))))
+            .unwrap();
+        planner
+            .add_dynamic(flexible("laundry", 120, PartOfDay::Morning).priority(1))
+            .unwrap();
+        planner.set_clock(clock);
+        planner
+    });
+
+    let [kept, rest @ ..] = &mut planners;
+    // unless a policy is chosen, nothing changes
+    assert!(!kept.advance());
+    assert_eq!(*kept.get_dynamic_tasks()[0].get_date(), monday());
+    assert_eq!(kept.get_dynamic_tasks()[0].get_priority(), 1);
+    assert_eq!(kept.overdue().len(), 1);
+    assert_eq!(kept.overdue()[0].1, monday());
+    for planner in rest {
+        assert!(planner.advance());
+        assert!(!planner.advance());
+    }
+    let [_, dropped, moved, overdue] = &planners;
+
+    assert!(dropped.get_dynamic_tasks().is_empty());
+    assert!(dropped.overdue().is_empty());
+
+    // wednesday is full, so it goes on thursday
+    assert_eq!(
+        *moved.get_dynamic_tasks()[0].get_date(),
+        wednesday + Duration::days(1)
+    );
+    assert_eq!(moved.overdue()[0].1, monday());
+
+    let task = &overdue.get_dynamic_tasks()[0];
+    assert_eq!(*task.get_date(), wednesday);
+    assert_eq!(task.get_priority(), 3);
+    assert_eq!(overdue.overdue()[0].1, monday());
+}
+
+#[test]
+fn split_task_fits_into_a_day_the_clocks_are_set_back_on() {
+    let config = Config::new(time(20, 0), time(4, 0)).time_zone(Tz::Europe__Prague);
+    // clocks go from 03:00 back to 02:00 in the night after
+    let date = NaiveDate::from_ymd_opt(2023, 10, 28).unwrap();
+    for strategy in [Strategy::FirstFit, Strategy::Optimal] {
+        let mut planner = planner_with(config.clone().strategy(strategy));
+        planner
+            .add_dynamic(DynamicTask::new_flexible(
+                task("study"),
+                date,
+                Duration::hours(8),
+                PartOfDay::Night,
+                true,
+            ))
+            .unwrap();
+
+        let (schedule, diagnostics) = planner.schedule_for(date);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| !diagnostic.is_unplaced()));
+        let placed = placed(&schedule, "study")
+            .iter()
+            .map(TimeRange::to_duration)
+            .fold(Duration::zero(), |total, part| total + part);
+        assert_eq!(placed, Duration::hours(8));
+    }
+}
+
+#[test]
+fn breaks_after_work_come_between_placed_tasks() {
+    let config = Config::new(time(6, 0), time(22, 0))
+        .time_zone(Tz::UTC)
+        .protect(Break::every(
+            "pause".to_string(),
+            Duration::minutes(90),
+            Duration::minutes(10),
+        ));
+    let breaks = |schedule: &[Schedule]| {
+        schedule
+            .iter()
+            .filter_map(|entry| match entry {
+                Schedule::Break(_, time) => Some(*time),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // a task longer than the work between breaks still fits into an empty day
+    let mut planner = planner_with(config.clone());
+    planner
+        .add_dynamic(flexible("essay", 120, PartOfDay::Morning))
+        .unwrap();
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "essay"), [range((6, 0), (8, 0))]);
+    assert!(breaks(&schedule).is_empty());
+    assert_eq!(free(&schedule), [range((8, 0), (22, 0))]);
+
+    // static tasks count as work too
+    let mut planner = planner_with(config);
+    planner
+        .add_static(0, StaticTask::new(task("gym"), range((6, 0), (7, 0))))
+        .unwrap();
+    for name in ["email", "review"] {
+        planner
+            .add_dynamic(flexible(name, 60, PartOfDay::Morning))
+            .unwrap();
+    }
+    let (schedule, diagnostics) = planner.schedule_for(monday());
+    assert!(diagnostics.is_empty());
+    assert_eq!(placed(&schedule, "email"), [range((7, 10), (8, 10))]);
+    assert_eq!(placed(&schedule, "review"), [range((8, 20), (9, 20))]);
+    assert_eq!(
+        breaks(&schedule),
+        [range((7, 0), (7, 10)), range((8, 10), (8, 20))]
+    );
+}
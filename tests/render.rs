@@ -0,0 +1,95 @@
+mod common;
+
+use chrono::Duration;
+use common::{monday, range};
+use lunite::{
+    render::{DayView, WeekView},
+    DynamicTask, PartOfDay, Schedule, StaticTask, Task,
+};
+
+fn task(name: &str) -> Task {
+    Task::new(name.to_string(), String::new())
+}
+
+fn study() -> DynamicTask {
+    DynamicTask::new_flexible(
+        task("study"),
+        monday(),
+        Duration::hours(3),
+        PartOfDay::Morning,
+        true,
+    )
+}
+
+/// The `nth` part of `of`, the hour from `start` o'clock.
+fn part<'a>(of: &'a DynamicTask, nth: usize, start: u32) -> Schedule<'a> {
+    Schedule::DynamicPart {
+        part: Box::new(of.fixed_split(&Duration::hours(1), nth).unwrap()),
+        of,
+        nth,
+        time: range((start, 0), (start + 1, 0)),
+    }
+}
+
+#[test]
+fn day_view_numbers_parts() {
+    let gym = StaticTask::new(task("gym"), range((9, 0), (10, 0)));
+    let study = study();
+    let schedule = vec![
+        part(&study, 1, 7),
+        Schedule::Static(&gym),
+        part(&study, 2, 10),
+        Schedule::Buffer(range((11, 0), (11, 15))),
+        part(&study, 3, 12),
+        Schedule::Free(range((13, 0), (22, 0))),
+    ];
+
+    assert_eq!(
+        DayView::new(&schedule).to_string(),
+        [
+            "07:00–08:00  study (part 1/3)",
+            "09:00–10:00  gym",
+            "10:00–11:00  study (part 2/3)",
+            "11:00–11:15  buffer",
+            "12:00–13:00  study (part 3/3)",
+            "13:00–22:00  free",
+            "",
+        ]
+        .join("\n")
+    );
+
+    // a task placed in one piece isn't numbered
+    let schedule = vec![part(&study, 1, 7)];
+    assert_eq!(DayView::new(&schedule).to_string(), "07:00–08:00  study\n");
+}
+
+#[test]
+fn week_view_lines_days_up_in_columns() {
+    let gym = StaticTask::new(task("gym"), range((9, 0), (10, 0)));
+    let study = study();
+    let days = vec![
+        vec![
+            Schedule::Static(&gym),
+            part(&study, 1, 10),
+            part(&study, 2, 12),
+        ],
+        vec![],
+        vec![Schedule::Free(range((6, 0), (22, 0)))],
+    ];
+
+    // entries longer than the column are cut off, empty cells are padded
+    assert_eq!(
+        WeekView::new(&days)
+            .width(16)
+            .to_string()
+            .lines()
+            .collect::<Vec<_>>(),
+        [
+            "Mon               Tue               Wed",
+            "----------------  ----------------  ----------------",
+            "09:00–10:00 gym                     06:00–22:00 free",
+            "10:00–11:00 stu…",
+            "12:00–13:00 stu…",
+        ]
+    );
+}
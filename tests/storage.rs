@@ -0,0 +1,130 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{monday, planner, range, time};
+use lunite::{LuniteError, Planner, StaticTask, Task, PLANNER_VERSION};
+
+const GYM: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+/// A version 0 planner, a bare planner without the version envelope keeping completions of
+/// static tasks on the weekday.
+fn version_0() -> String {
+    let empty = r#"{ "static_tasks": [], "static_done": [], "dynamic_tasks": [] }"#;
+    let monday = format!(
+        r#"{{
+            "static_tasks": [{{
+                "task": {{ "uuid": "{GYM}", "name": "gym", "description": "" }},
+                "time": {{ "start": "07:00:00", "end": "08:00:00" }}
+            }}],
+            "static_done": [["{GYM}", "2023-05-01T07:55:00"]],
+            "dynamic_tasks": []
+        }}"#
+    );
+    format!(
+        r#"{{
+            "config": {{ "wake_time": "06:00:00", "bed_time": "22:00:00" }},
+            "days": [{monday}, {empty}, {empty}, {empty}, {empty}, {empty}, {empty}],
+            "dynamic_tasks": [],
+            "dynamic_done": []
+        }}"#
+    )
+}
+
+fn version_1() -> String {
+    format!(r#"{{ "version": 1, "planner": {} }}"#, version_0())
+}
+
+/// Checks `planner` is [`version_0`] migrated to the current version.
+fn assert_migrated(planner: &Planner) {
+    let gym = &planner.nth_day(0).unwrap().get_static_tasks()[0];
+    assert_eq!(gym.get_task().get_name(), "gym");
+    assert_eq!(*gym.get_time(), range((7, 0), (8, 0)));
+
+    let plan = planner.get_day_plan(monday()).unwrap();
+    assert_eq!(
+        plan.get_done(),
+        [(GYM.parse().unwrap(), monday().and_time(time(7, 55)))]
+    );
+}
+
+#[test]
+fn saved_planner_loads_back() {
+    let mut planner = planner();
+    planner
+        .add_static(
+            0,
+            StaticTask::new(
+                Task::new("gym".to_string(), String::new()),
+                range((7, 0), (8, 0)),
+            ),
+        )
+        .unwrap();
+    let dir = std::env::temp_dir().join(format!("lunite-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("planner.json");
+
+    planner.save(&path).unwrap();
+    let loaded = Planner::load(&path).unwrap();
+    let files = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(files, 1);
+    assert_eq!(
+        loaded.nth_day(0).unwrap().get_static_tasks(),
+        planner.nth_day(0).unwrap().get_static_tasks()
+    );
+    assert_eq!(
+        loaded.get_config().get_day(),
+        planner.get_config().get_day()
+    );
+}
+
+#[test]
+fn planner_is_saved_in_a_version_envelope() {
+    let json: serde_json::Value = serde_json::from_str(&planner().to_json().unwrap()).unwrap();
+
+    assert_eq!(json["version"], PLANNER_VERSION);
+    assert!(json["planner"]["days"].is_array());
+}
+
+#[test]
+fn version_0_planner_is_migrated() {
+    assert_migrated(&Planner::from_json(&version_0()).unwrap());
+}
+
+#[test]
+fn version_1_planner_is_migrated() {
+    let planner = Planner::from_json(&version_1()).unwrap();
+
+    assert_migrated(&planner);
+    // the migrated planner is saved as the current version
+    let saved = Planner::from_json(&planner.to_json().unwrap()).unwrap();
+    assert_migrated(&saved);
+    assert!(saved
+        .get_day_plan(NaiveDate::from_ymd_opt(2023, 5, 8).unwrap())
+        .is_none());
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    let json = format!(
+        r#"{{ "version": {}, "planner": {} }}"#,
+        PLANNER_VERSION + 1,
+        version_0()
+    );
+
+    assert!(matches!(
+        Planner::from_json(&json),
+        Err(LuniteError::UnsupportedVersion(version)) if version == PLANNER_VERSION + 1
+    ));
+}
+
+#[test]
+fn broken_version_1_planner_fails_to_migrate() {
+    let json = r#"{ "version": 1, "planner": { "config": {} } }"#;
+
+    assert!(matches!(
+        Planner::from_json(json),
+        Err(LuniteError::Migration { version: 1, .. })
+    ));
+}
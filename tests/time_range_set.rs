@@ -0,0 +1,120 @@
+mod common;
+
+use common::{range, time};
+use lunite::{TimeRange, TimeRangeSet};
+use proptest::prelude::*;
+
+fn set(ranges: &[TimeRange]) -> TimeRangeSet {
+    ranges.iter().copied().collect()
+}
+
+#[test]
+fn ranges_merge_over_midnight() {
+    let busy = set(&[
+        range((23, 0), (1, 0)),
+        range((0, 30), (2, 0)),
+        range((9, 0), (10, 0)),
+        range((10, 0), (11, 0)),
+    ]);
+
+    assert_eq!(
+        busy.iter().collect::<Vec<_>>(),
+        [range((9, 0), (11, 0)), range((23, 0), (2, 0))]
+    );
+    assert_eq!(busy.to_duration(), chrono::Duration::hours(5));
+    assert_eq!(
+        busy.gaps(&range((20, 0), (10, 0))),
+        [range((20, 0), (23, 0)), range((2, 0), (9, 0))]
+    );
+    assert_eq!(
+        busy.within(&range((20, 0), (10, 0))),
+        [range((23, 0), (2, 0)), range((9, 0), (10, 0))]
+    );
+    assert_eq!(
+        busy.difference(&set(&[range((0, 0), (12, 0))]))
+            .iter()
+            .collect::<Vec<_>>(),
+        [range((23, 0), (0, 0))]
+    );
+    assert_eq!(
+        busy.intersection(&set(&[range((10, 30), (23, 30))]))
+            .iter()
+            .collect::<Vec<_>>(),
+        [range((10, 30), (11, 0)), range((23, 0), (23, 30))]
+    );
+}
+
+#[test]
+fn subset_needs_the_whole_range_inside() {
+    let day = range((6, 0), (2, 0));
+
+    assert!(range((23, 0), (1, 0)).subset(&day));
+    assert!(!range((5, 0), (7, 0)).subset(&day));
+    assert!(!range((1, 0), (3, 0)).subset(&day));
+    assert!(!day.subset(&range((7, 0), (8, 0))));
+}
+
+fn ranges() -> impl Strategy<Value = Vec<TimeRange>> {
+    prop::collection::vec((0..1440u32, 1..1440u32), 0..5).prop_map(|ranges| {
+        ranges
+            .into_iter()
+            .map(|(start, length)| TimeRange::new(minutes(start), minutes(start + length)))
+            .collect()
+    })
+}
+
+fn minutes(minutes: u32) -> chrono::NaiveTime {
+    time(minutes / 60 % 24, minutes % 60)
+}
+
+/// Whether the minute starting `minute` after midnight is in any of `ranges`.
+fn covers(ranges: &[TimeRange], minute: u32) -> bool {
+    let minute = TimeRange::new(minutes(minute), minutes(minute + 1));
+    ranges.iter().any(|range| range.overlap(&minute))
+}
+
+proptest! {
+    #[test]
+    fn set_operations_agree_with_every_minute(a in ranges(), b in ranges()) {
+        let (set_a, set_b) = (set(&a), set(&b));
+        let union = set_a.union(&set_b).iter().collect::<Vec<_>>();
+        let intersection = set_a.intersection(&set_b).iter().collect::<Vec<_>>();
+        let difference = set_a.difference(&set_b).iter().collect::<Vec<_>>();
+
+        let mut total = 0;
+        for minute in 0..1440 {
+            let (in_a, in_b) = (covers(&a, minute), covers(&b, minute));
+            prop_assert_eq!(covers(&union, minute), in_a || in_b);
+            prop_assert_eq!(covers(&intersection, minute), in_a && in_b);
+            prop_assert_eq!(covers(&difference, minute), in_a && !in_b);
+            total += u32::from(in_a);
+        }
+        prop_assert_eq!(set_a.to_duration().num_minutes(), i64::from(total));
+    }
+
+    #[test]
+    fn gaps_and_ranges_within_make_up_the_window(
+        ranges in ranges(),
+        start in 0..1440u32,
+        length in 1..=1440u32,
+    ) {
+        let set = set(&ranges);
+        let window = TimeRange::new(minutes(start), minutes(start + length));
+        let gaps = set.gaps(&window);
+        let within = set.within(&window);
+
+        for part in gaps.iter().chain(&within) {
+            prop_assert!(part.subset(&window));
+        }
+        for minute in start..start + length {
+            prop_assert_eq!(covers(&within, minute % 1440), covers(&ranges, minute % 1440));
+            prop_assert_eq!(covers(&gaps, minute % 1440), !covers(&ranges, minute % 1440));
+        }
+        // in the order of the window
+        let offsets = gaps
+            .iter()
+            .map(|gap| (gap.get_start() - window.get_start()).num_minutes().rem_euclid(1440))
+            .collect::<Vec<_>>();
+        prop_assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}